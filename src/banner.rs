@@ -1,18 +1,22 @@
-use crate::task_ui::run;
+use crate::idle;
+use crate::tui::{EventSource, run};
 use crate::util::App;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
 use ratatui::layout::Alignment;
 use ratatui::prelude::*;
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, Borders, Padding, Paragraph};
-use ratatui::{
-    style::{Color, Modifier, Style},
-    DefaultTerminal,
-};
 
 use std::time::Duration;
 
-pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::error::Error>> {
+/// Shows the welcome screen until D opens the dashboard. Returns the app to
+/// save, or `None` when Esc left before the dashboard was opened.
+pub fn tui_banner<B: Backend>(
+    terminal: &mut Terminal<B>,
+    task_ui_app_instance: App,
+    events: &mut impl EventSource,
+) -> Result<Option<App>, Box<dyn std::error::Error>> {
     let banner_ascii = r#"
  ██████╗██╗  ██╗██████╗  ██████╗ ███╗   ██╗ ██████╗ ███████╗
 ██╔════╝██║  ██║██╔══██╗██╔═══██╗████╗  ██║██╔═══██╗██╔════╝
@@ -21,8 +25,9 @@ pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::erro
 ╚██████╗██║  ██║██║  ██║╚██████╔╝██║ ╚████║╚██████╔╝███████║
  ╚═════╝╚═╝  ╚═╝╚═╝  ╚═╝ ╚═════╝ ╚═╝  ╚═══╝ ╚═════╝ ╚══════╝
 "#;
-    let task_ui_app_instance = App::new();
 
+    let colors = task_ui_app_instance.config.colors;
+    let wait = Duration::from_millis(task_ui_app_instance.config.ui.banner_wait_ms);
     loop {
         terminal.draw(|frame| {
             let layout = Layout::default()
@@ -32,7 +37,7 @@ pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::erro
 
             // Background
             frame.render_widget(
-                Block::default().style(Style::default().bg(Color::Rgb(10, 14, 32))),
+                Block::default().style(Style::default().bg(colors.background)),
                 layout[0],
             );
 
@@ -58,12 +63,7 @@ pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::erro
             // Split ASCII into separate lines
             let mut banner_lines: Vec<Line> = banner_ascii
                 .lines()
-                .map(|l| {
-                    Line::from(Span::styled(
-                        l,
-                        Style::default().fg(Color::Rgb(255, 165, 0)),
-                    ))
-                })
+                .map(|l| Line::from(Span::styled(l, Style::default().fg(colors.heading))))
                 .collect();
 
             // Add spacing after ASCII
@@ -73,19 +73,19 @@ pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::erro
             banner_lines.extend(vec![
                 Line::from(Span::styled(
                     "Welcome to Chronos, your personal time tracker.",
-                    Style::default().fg(Color::Rgb(0, 200, 180)),
+                    Style::default().fg(colors.text),
                 )),
                 Line::from(Span::styled(
                     "Set new tasks and keep records of your productivity at your fingertips.",
-                    Style::default().fg(Color::Rgb(0, 200, 180)),
+                    Style::default().fg(colors.text),
                 )),
                 Line::from(Span::styled(
                     "Let's get started, shall we?",
-                    Style::default().fg(Color::Rgb(0, 200, 180)),
+                    Style::default().fg(colors.text),
                 )),
                 Line::from(Span::styled(
                     "Jump to dashboard by pressing D on your keyboard.",
-                    Style::default().fg(Color::Rgb(0, 200, 180)),
+                    Style::default().fg(colors.text),
                 )),
                 Line::from(""), // extra space
                 Line::from(Span::styled(
@@ -105,13 +105,13 @@ pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::erro
                 Line::from(""),
                 Line::from(Span::styled(
                     "Press <D> to jump to DASHBOARD",
-                    Style::default().fg(Color::Rgb(255, 165, 0)),
+                    Style::default().fg(colors.heading),
                 )),
             ]);
 
             let banner_widget = Paragraph::new(Text::from(banner_lines))
                 .alignment(Alignment::Center)
-                .style(Style::default().fg(Color::Rgb(255, 165, 0)))
+                .style(Style::default().fg(colors.heading))
                 .block(
                     Block::new()
                         .title("BANNER")
@@ -122,20 +122,20 @@ pub fn tui_banner(mut terminal: DefaultTerminal) -> Result<(), Box<dyn std::erro
             frame.render_widget(banner_widget, banner_block[1]);
         })?;
 
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('D') => {
-                            // Exit tui_banner loop and hand over the terminal to run()
-                            return run(terminal, task_ui_app_instance);
-                        }
-                        KeyCode::Esc => {
-                            break Ok(());
-                        }
-                        _ => {}
-                    }
+        if let Some(Event::Key(key)) = events.next(wait)?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('D') => {
+                    // Exit tui_banner loop and hand over the terminal to run()
+                    let mut app = task_ui_app_instance;
+                    idle::restart(&mut app);
+                    return run(terminal, app, events).map(Some);
                 }
+                KeyCode::Esc => {
+                    break Ok(None);
+                }
+                _ => {}
             }
         }
     }
@@ -0,0 +1,890 @@
+use crate::clock;
+use crate::config::Config;
+use crate::export::{self, Dataset, Format};
+use crate::report::{self, Breakdown, Period, PeriodTotal};
+use crate::storage;
+use crate::tracker::{Event, TaskId, TaskSpec, TaskStatus, Tracker};
+use crate::util::{format_hms, parse_duration, parse_when};
+
+use chrono::{DateTime, Days, Local, NaiveDate};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Chronos, a terminal time tracker. Run without a subcommand to open the TUI.
+#[derive(Parser)]
+#[command(name = "chronos", version, about)]
+pub struct Cli {
+    /// Print machine-readable JSON instead of plain text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// How often the TUI redraws running timers, in milliseconds
+    #[arg(long, default_value_t = 1000, value_parser = clap::value_parser!(u64).range(50..))]
+    pub tick_rate: u64,
+
+    /// Debug: run the TUI on a clock SPEED times faster than real time, up
+    /// to 3600, and allow `:skip`. Nothing from the session is saved
+    #[arg(
+        long,
+        value_name = "SPEED",
+        value_parser = clap::value_parser!(u32).range(1..=clock::MAX_SPEED as i64)
+    )]
+    pub simulate: Option<u32>,
+
+    /// Read settings from this file instead of
+    /// $XDG_CONFIG_HOME/chronos/config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start tracking a task, creating it if it does not exist yet
+    Start {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+    },
+    /// Pause a running task and start its break timer
+    Pause {
+        /// Task name or id; pauses every running task when omitted
+        name: Vec<String>,
+        /// Note attached to the interval that just ended
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Resume a paused task
+    Resume {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+    },
+    /// Stop a running task without starting a break
+    Stop {
+        /// Task name or id; stops every running task when omitted
+        name: Vec<String>,
+        /// Note attached to the interval that just ended
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Show the tasks that are currently running
+    Status,
+    /// List every task with its tracked time
+    List {
+        /// Only tasks in this project
+        #[arg(long)]
+        project: Option<String>,
+        /// Only tasks carrying this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Show tracked time per day, week or month
+    Report {
+        /// First day to include (YYYY-MM-DD), defaults to a week ago
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to include (YYYY-MM-DD), defaults to today
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// Period the totals are summed over
+        #[arg(long, value_enum, default_value_t = Period::Day)]
+        group_by: Period,
+        /// Split each period total by task or by project
+        #[arg(long, value_enum, default_value_t = Breakdown::Task)]
+        breakdown: Breakdown,
+    },
+    /// Export tasks, intervals, breaks or daily totals as CSV or JSON
+    Export {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        /// What to export; defaults to intervals for CSV and everything for JSON
+        #[arg(long, value_enum)]
+        dataset: Option<Dataset>,
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long)]
+        from: Option<NaiveDate>,
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long)]
+        to: Option<NaiveDate>,
+        /// File to write instead of standard output
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Move a task and its tracked intervals to the trash
+    Delete {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+    },
+    /// Give a task a new name
+    Rename {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+        /// The new name
+        #[arg(long, required = true)]
+        to: String,
+    },
+    /// Fold a task's intervals, breaks, estimate and tags into another task
+    Merge {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+        /// Task that absorbs it
+        #[arg(long, required = true)]
+        into: String,
+    },
+    /// Make a task a subtask of another, or top-level again without --under
+    Move {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+        /// The new parent
+        #[arg(long)]
+        under: Option<String>,
+    },
+    /// List deleted tasks
+    Trash {
+        /// Delete everything in the trash for good
+        #[arg(long)]
+        empty: bool,
+    },
+    /// Bring a deleted task back from the trash
+    Restore {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+    },
+    /// List, add or fix logged intervals of tracked time
+    #[command(subcommand)]
+    Intervals(IntervalCommand),
+}
+
+/// Intervals are addressed by the number `chronos intervals list` shows.
+/// Times are `HH:MM`, optionally preceded by `today`, `yesterday` or a
+/// `YYYY-MM-DD` date.
+#[derive(Subcommand)]
+pub enum IntervalCommand {
+    /// Show the interval log, flagging overlapping entries with `!`
+    List {
+        /// Only the intervals of this task
+        name: Vec<String>,
+    },
+    /// Log past work on a task
+    Add {
+        #[arg(required = true, num_args = 1..)]
+        name: Vec<String>,
+        /// When the work started; defaults to `--duration` before the end
+        #[arg(long)]
+        start: Option<String>,
+        /// When the work ended; defaults to now, or `--duration` after the start
+        #[arg(long)]
+        end: Option<String>,
+        /// How long the work took, e.g. 2h or 45m
+        #[arg(long)]
+        duration: Option<String>,
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Move the start and/or end of an interval
+    Edit {
+        number: usize,
+        #[arg(long)]
+        start: Option<String>,
+        #[arg(long)]
+        end: Option<String>,
+    },
+    /// Cut an interval in two
+    Split {
+        number: usize,
+        /// Where to cut it
+        #[arg(long)]
+        at: String,
+    },
+    /// Remove an interval from the log
+    Delete { number: usize },
+}
+
+#[derive(Serialize)]
+struct TaskView {
+    id: TaskId,
+    number: u32,
+    name: String,
+    status: TaskStatus,
+    time_spent_secs: u64,
+    /// Time spent including subtasks.
+    total_secs: u64,
+    expected_secs: u64,
+    project: Option<String>,
+    tags: Vec<String>,
+    started_at: Option<DateTime<Local>>,
+    parent: Option<TaskId>,
+}
+
+/// Runs one subcommand against the on-disk store and prints the result.
+/// The store is only written when the subcommand changed something.
+pub fn run_command(
+    command: Command,
+    json: bool,
+    config: Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = storage::data_path();
+    let mut tracker = storage::load(&path)?;
+    tracker.configure(&config);
+
+    let mut out = Vec::new();
+    if execute(command, json, &mut tracker, &mut out)? {
+        storage::save(&path, &tracker)?;
+    }
+    io::stdout().write_all(&out)?;
+    Ok(())
+}
+
+/// Applies a subcommand to `tracker`, writing what it has to say to `out`.
+/// Returns whether anything changed that needs saving.
+fn execute(
+    command: Command,
+    json: bool,
+    tracker: &mut Tracker,
+    out: &mut impl Write,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let listing = matches!(command, Command::List { .. });
+    let changes = !matches!(command, Command::Status | Command::List { .. });
+    let touched: Vec<usize> = match command {
+        Command::Start { name } => {
+            let i = tracker.start(TaskSpec::parse(&name.join(" ")))?;
+            switched(tracker, i)
+        }
+        Command::Pause { name, note } => {
+            let targets = running_targets(tracker, &name)?;
+            for &i in &targets {
+                tracker.pause(i, note.clone());
+            }
+            targets
+        }
+        Command::Stop { name, note } => {
+            let targets = running_targets(tracker, &name)?;
+            for &i in &targets {
+                tracker.stop(i, note.clone());
+            }
+            targets
+        }
+        Command::Resume { name } => {
+            let i = lookup(tracker, &name.join(" "))?;
+            if matches!(tracker.tasks[i].status, TaskStatus::Active) {
+                return Err(format!("'{}' is already running", tracker.tasks[i].name).into());
+            }
+            tracker.resume(i);
+            switched(tracker, i)
+        }
+        Command::Status => tracker.running(),
+        Command::List { project, tag } => tracker
+            .tasks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| project.is_none() || t.project == project)
+            .filter(|(_, t)| tag.as_ref().is_none_or(|tag| t.tags.contains(tag)))
+            .map(|(i, _)| i)
+            .collect(),
+        Command::Report {
+            from,
+            to,
+            group_by,
+            breakdown,
+        } => {
+            let to = to.unwrap_or_else(|| tracker.now().date_naive());
+            let from = from.unwrap_or(to - Days::new(6));
+            if from > to {
+                return Err("--from must not be after --to".into());
+            }
+            print_report(
+                &report::build(tracker, from, to, group_by, breakdown),
+                json,
+                out,
+            )?;
+            return Ok(false);
+        }
+        Command::Export {
+            format,
+            dataset,
+            from,
+            to,
+            output,
+        } => {
+            if let (Some(from), Some(to)) = (from, to)
+                && from > to
+            {
+                return Err("--from must not be after --to".into());
+            }
+            let dataset = dataset.unwrap_or(format.default_dataset());
+            let contents = export::render(tracker, from, to, format, dataset)?;
+            match output {
+                Some(path) => {
+                    export::write(&path, &contents)?;
+                    eprintln!("Wrote {}", path.display());
+                }
+                None => write!(out, "{contents}")?,
+            }
+            return Ok(false);
+        }
+        Command::Intervals(action) => {
+            let listing = matches!(action, IntervalCommand::List { .. });
+            let shown = run_interval_command(tracker, action)?;
+            print_intervals(tracker, &shown, json, out)?;
+            return Ok(!listing);
+        }
+        Command::Delete { name } => {
+            let i = lookup(tracker, &name.join(" "))?;
+            let task = tracker.tasks[i].clone();
+            tracker.delete(i);
+            if json {
+                writeln!(
+                    out,
+                    "{}",
+                    serde_json::json!({ "deleted": task.id, "number": task.number })
+                )?;
+            } else {
+                writeln!(
+                    out,
+                    "Moved task {} ({}) to the trash",
+                    task.number, task.name
+                )?;
+            }
+            return Ok(true);
+        }
+        Command::Rename { name, to } => {
+            let i = lookup(tracker, &name.join(" "))?;
+            tracker.rename(i, &to)?;
+            vec![i]
+        }
+        Command::Merge { name, into } => {
+            let from = lookup(tracker, &name.join(" "))?;
+            let into = lookup(tracker, &into)?;
+            vec![tracker.merge(from, into)?]
+        }
+        Command::Move { name, under } => {
+            let i = lookup(tracker, &name.join(" "))?;
+            let parent = under.map(|p| lookup(tracker, &p)).transpose()?;
+            tracker.set_parent(i, parent)?;
+            vec![i]
+        }
+        Command::Trash { empty } => {
+            if empty {
+                let count = tracker.trash.len();
+                tracker.trash.clear();
+                if json {
+                    writeln!(out, "{}", serde_json::json!({ "purged": count }))?;
+                } else {
+                    writeln!(out, "Emptied the trash ({count} tasks)")?;
+                }
+            } else if json {
+                let rows: Vec<_> = tracker
+                    .trash
+                    .iter()
+                    .map(|t| {
+                        serde_json::json!({
+                            "id": t.task.id,
+                            "number": t.task.number,
+                            "name": t.task.name,
+                            "intervals": t.intervals.len(),
+                            "deleted_at": t.deleted_at.to_rfc3339(),
+                        })
+                    })
+                    .collect();
+                writeln!(out, "{}", serde_json::to_string_pretty(&rows)?)?;
+            } else if tracker.trash.is_empty() {
+                writeln!(out, "The trash is empty")?;
+            } else {
+                for t in &tracker.trash {
+                    writeln!(
+                        out,
+                        "{:>4}  {:<30} {:>3} intervals  deleted {}",
+                        t.task.number,
+                        t.task.name,
+                        t.intervals.len(),
+                        t.deleted_at.format("%Y-%m-%d %H:%M")
+                    )?;
+                }
+            }
+            return Ok(empty);
+        }
+        Command::Restore { name } => {
+            let query = name.join(" ");
+            let n = tracker
+                .find_trashed(&query)
+                .ok_or_else(|| format!("no task named '{query}' in the trash"))?;
+            vec![tracker.restore(n)?]
+        }
+    };
+
+    print_tasks(tracker, &touched, json, out)?;
+    if !json && listing {
+        let (estimated, actual) = tracker.estimate_summary();
+        writeln!(
+            out,
+            "Estimated {} | Actual {}",
+            format_hms(estimated),
+            format_hms(actual)
+        )?;
+    }
+    Ok(changes)
+}
+
+/// Applies an interval subcommand and returns the intervals worth showing
+/// afterwards.
+fn run_interval_command(
+    tracker: &mut Tracker,
+    action: IntervalCommand,
+) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    let now = tracker.now();
+    let today = now.date_naive();
+    let when = |input: &str, day: NaiveDate| {
+        parse_when(input, day, today).ok_or_else(|| format!("cannot read '{input}' as a time"))
+    };
+    let task_intervals = |tracker: &Tracker, task_id: TaskId| -> Vec<usize> {
+        (0..tracker.intervals.len())
+            .filter(|&n| tracker.intervals[n].task_id == task_id)
+            .collect()
+    };
+
+    match action {
+        IntervalCommand::List { name } => {
+            if name.is_empty() {
+                return Ok((0..tracker.intervals.len()).collect());
+            }
+            let i = lookup(tracker, &name.join(" "))?;
+            Ok(task_intervals(tracker, tracker.tasks[i].id))
+        }
+        IntervalCommand::Add {
+            name,
+            start,
+            end,
+            duration,
+            note,
+        } => {
+            let i = lookup(tracker, &name.join(" "))?;
+            let duration = match duration {
+                Some(d) => Some(
+                    parse_duration(&d).ok_or_else(|| format!("cannot read '{d}' as a duration"))?,
+                ),
+                None => None,
+            };
+            let start = start.map(|s| when(&s, today)).transpose()?;
+            let day = start.map_or(today, |s| s.date_naive());
+            let end_has_day = end.as_ref().is_some_and(|e| e.trim().contains(' '));
+            let end = end.map(|e| when(&e, day)).transpose()?;
+            let (start, end) = match (start, end, duration) {
+                // A bare end time before the start means the work ran past midnight
+                (Some(start), Some(end), _) if end <= start && !end_has_day => {
+                    (start, end + Days::new(1))
+                }
+                (Some(start), Some(end), _) => (start, end),
+                (Some(start), None, Some(d)) => (start, start + d),
+                (None, end, Some(d)) => {
+                    let end = end.unwrap_or(now);
+                    (end - d, end)
+                }
+                _ => return Err("give --start with --end or --duration, or just --duration".into()),
+            };
+            let task_id = tracker.tasks[i].id;
+            tracker.add_interval(task_id, start, end, note)?;
+            Ok(task_intervals(tracker, task_id))
+        }
+        IntervalCommand::Edit { number, start, end } => {
+            let n = interval_index(number)?;
+            let day = tracker
+                .intervals
+                .get(n)
+                .map_or(today, |iv| iv.start.date_naive());
+            let start = start.map(|s| when(&s, day)).transpose()?;
+            let end = end.map(|e| when(&e, day)).transpose()?;
+            let n = tracker.edit_interval(n, start, end)?;
+            Ok(task_intervals(tracker, tracker.intervals[n].task_id))
+        }
+        IntervalCommand::Split { number, at } => {
+            let n = interval_index(number)?;
+            let day = tracker
+                .intervals
+                .get(n)
+                .map_or(today, |iv| iv.start.date_naive());
+            let second = tracker.split_interval(n, when(&at, day)?)?;
+            Ok(task_intervals(tracker, tracker.intervals[second].task_id))
+        }
+        IntervalCommand::Delete { number } => {
+            let removed = tracker.delete_interval(interval_index(number)?)?;
+            Ok(task_intervals(tracker, removed.task_id))
+        }
+    }
+}
+
+/// Turns the number `chronos intervals list` shows into a position in the
+/// log. There is no interval #0.
+fn interval_index(number: usize) -> Result<usize, Box<dyn std::error::Error>> {
+    number
+        .checked_sub(1)
+        .ok_or_else(|| "intervals are numbered from 1".into())
+}
+
+/// The task that was just started followed by the ones the single-active
+/// policy paused for it.
+fn switched(tracker: &mut Tracker, started: usize) -> Vec<usize> {
+    let mut touched = vec![started];
+    for event in tracker.take_events() {
+        if let Event::Stopped(id) = event
+            && let Some(i) = tracker.position(id)
+        {
+            touched.push(i);
+        }
+    }
+    touched
+}
+
+fn lookup(tracker: &Tracker, query: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    tracker
+        .find(query)
+        .ok_or_else(|| format!("no task named '{query}'").into())
+}
+
+/// Resolves the task a pause/stop applies to: the named one, or every
+/// running task when no name was given.
+fn running_targets(
+    tracker: &Tracker,
+    name: &[String],
+) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    if name.is_empty() {
+        let running = tracker.running();
+        if running.is_empty() {
+            return Err("no task is running".into());
+        }
+        return Ok(running);
+    }
+
+    let i = lookup(tracker, &name.join(" "))?;
+    if matches!(tracker.tasks[i].status, TaskStatus::Paused) {
+        return Err(format!("'{}' is not running", tracker.tasks[i].name).into());
+    }
+    Ok(vec![i])
+}
+
+fn print_tasks(
+    tracker: &Tracker,
+    indices: &[usize],
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        let views: Vec<TaskView> = indices
+            .iter()
+            .map(|&i| {
+                let task = &tracker.tasks[i];
+                TaskView {
+                    id: task.id,
+                    number: task.number,
+                    name: task.name.clone(),
+                    status: task.status.clone(),
+                    time_spent_secs: tracker.time_spent(task).as_secs(),
+                    total_secs: tracker.rolled_up_time(task).as_secs(),
+                    expected_secs: task.expected_duration.as_secs(),
+                    project: task.project.clone(),
+                    tags: task.tags.clone(),
+                    started_at: task.started_at,
+                    parent: task.parent,
+                }
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&views)?)?;
+        return Ok(());
+    }
+
+    if indices.is_empty() {
+        writeln!(out, "No tasks.")?;
+    }
+    for &i in indices {
+        let task = &tracker.tasks[i];
+        let flag = if tracker.over_budget(task).is_some() {
+            "!"
+        } else {
+            " "
+        };
+        let path = tracker.task_path(task);
+        writeln!(
+            out,
+            "{:>4}  {:<8}  {} / {}{}  {}{}",
+            task.number,
+            format!("{:?}", task.status),
+            format_hms(tracker.rolled_up_time(task)),
+            format_hms(task.expected_duration),
+            flag,
+            path.strip_suffix(task.name.as_str()).unwrap_or_default(),
+            task.label()
+        )?;
+    }
+    Ok(())
+}
+
+fn print_report(
+    totals: &[PeriodTotal],
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        let views: Vec<_> = totals
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "period": p.period,
+                    "total_secs": p.total.as_secs(),
+                    "entries": p.entries.iter().map(|(key, d)| {
+                        serde_json::json!({ "key": key, "secs": d.as_secs() })
+                    }).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&views)?)?;
+        return Ok(());
+    }
+
+    if totals.is_empty() {
+        writeln!(out, "Nothing tracked in this range.")?;
+        return Ok(());
+    }
+    for p in totals {
+        writeln!(out, "{:<30}  {}", p.period, format_hms(p.total))?;
+        for (key, d) in &p.entries {
+            writeln!(out, "  {:<28}  {}", key, format_hms(*d))?;
+        }
+    }
+    let total: std::time::Duration = totals.iter().map(|p| p.total).sum();
+    writeln!(out, "{:<30}  {}", "Total", format_hms(total))?;
+    Ok(())
+}
+
+fn print_intervals(
+    tracker: &Tracker,
+    shown: &[usize],
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let task_name = |task_id: TaskId| {
+        tracker
+            .tasks
+            .iter()
+            .find(|t| t.id == task_id)
+            .map_or("?", |t| t.name.as_str())
+    };
+
+    if json {
+        let views: Vec<_> = shown
+            .iter()
+            .map(|&n| {
+                let iv = &tracker.intervals[n];
+                serde_json::json!({
+                    "number": n + 1,
+                    "task_id": iv.task_id,
+                    "task": task_name(iv.task_id),
+                    "start": iv.start,
+                    "end": iv.end,
+                    "duration_secs": iv.duration().as_secs(),
+                    "note": iv.note,
+                    "overlaps": tracker.overlaps_with(n).iter().map(|m| m + 1).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&views)?)?;
+        return Ok(());
+    }
+
+    if shown.is_empty() {
+        writeln!(out, "No intervals.")?;
+    }
+    for &n in shown {
+        let iv = &tracker.intervals[n];
+        let overlaps = tracker.overlaps_with(n);
+        let flag = if overlaps.is_empty() { " " } else { "!" };
+        writeln!(
+            out,
+            "{:>4}{}  {:<36}  {}  {}{}",
+            n + 1,
+            flag,
+            iv.span(),
+            format_hms(iv.duration()),
+            task_name(iv.task_id),
+            iv.note
+                .as_ref()
+                .map(|note| format!(" ({note})"))
+                .unwrap_or_default()
+        )?;
+        if !overlaps.is_empty() {
+            let others: Vec<String> = overlaps.iter().map(|m| format!("#{}", m + 1)).collect();
+            writeln!(out, "        overlaps {}", others.join(", "))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    use chrono::TimeZone;
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A store-less tracker on a clock that stands at 9:00 until moved.
+    fn tracker() -> (Tracker, MockClock) {
+        let clock = MockClock::new(Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap());
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(clock.clone());
+        (tracker, clock)
+    }
+
+    /// Runs `chronos <args>` on `tracker`. Returns whether it would save
+    /// and what it printed.
+    fn chronos(tracker: &mut Tracker, args: &str) -> Result<(bool, String), String> {
+        let cli = Cli::try_parse_from(["chronos"].into_iter().chain(args.split_whitespace()))
+            .map_err(|err| err.to_string())?;
+        let mut out = Vec::new();
+        let changed = execute(cli.command.unwrap(), cli.json, tracker, &mut out)
+            .map_err(|err| err.to_string())?;
+        Ok((changed, String::from_utf8(out).unwrap()))
+    }
+
+    #[test]
+    fn start_pause_resume_and_stop_track_a_task() {
+        let (mut tracker, clock) = tracker();
+        let (changed, out) = chronos(&mut tracker, "start Write").unwrap();
+        assert!(changed);
+        assert!(out.contains("Active"), "{out}");
+        assert!(out.contains("Write"), "{out}");
+
+        clock.advance(Duration::from_secs(30 * 60));
+        let (_, out) = chronos(&mut tracker, "pause --note draft").unwrap();
+        assert!(out.contains("Paused"), "{out}");
+        assert!(out.contains("00:30:00"), "{out}");
+        assert_eq!(tracker.intervals[0].note.as_deref(), Some("draft"));
+        let id = tracker.tasks[0].id;
+        assert!(
+            tracker.breaks[&id].started_at.is_some(),
+            "a pause starts a break"
+        );
+
+        assert!(chronos(&mut tracker, "resume Write").unwrap().0);
+        assert_eq!(
+            chronos(&mut tracker, "resume Write").unwrap_err(),
+            "'Write' is already running"
+        );
+        clock.advance(Duration::from_secs(15 * 60));
+        let (_, out) = chronos(&mut tracker, "stop 1").unwrap();
+        assert!(out.contains("00:45:00"), "{out}");
+        assert!(
+            tracker.breaks[&id].started_at.is_none(),
+            "a stop starts no break"
+        );
+        assert_eq!(
+            chronos(&mut tracker, "stop").unwrap_err(),
+            "no task is running"
+        );
+    }
+
+    #[test]
+    fn status_and_list_change_nothing_to_save() {
+        let (mut tracker, _) = tracker();
+        chronos(&mut tracker, "start Write +docs ~1h").unwrap();
+        chronos(&mut tracker, "pause").unwrap();
+        chronos(&mut tracker, "start Review").unwrap();
+
+        let (changed, out) = chronos(&mut tracker, "status").unwrap();
+        assert!(!changed);
+        assert!(out.contains("Review") && !out.contains("Write"), "{out}");
+
+        let (changed, out) = chronos(&mut tracker, "list --project docs").unwrap();
+        assert!(!changed);
+        assert!(
+            out.contains("Write +docs") && !out.contains("Review"),
+            "{out}"
+        );
+        assert!(out.contains("Estimated "), "{out}");
+
+        assert!(!chronos(&mut tracker, "intervals list").unwrap().0);
+        assert!(!chronos(&mut tracker, "trash").unwrap().0);
+    }
+
+    #[test]
+    fn delete_moves_a_task_to_the_trash() {
+        let (mut tracker, _) = tracker();
+        chronos(&mut tracker, "start Write").unwrap();
+        chronos(&mut tracker, "start Review").unwrap();
+
+        let (changed, out) = chronos(&mut tracker, "delete Write").unwrap();
+        assert!(changed);
+        assert_eq!(out, "Moved task 1 (Write) to the trash\n");
+        assert_eq!(tracker.trash[0].task.name, "Write");
+
+        let id = tracker.tasks[0].id;
+        let (_, out) = chronos(&mut tracker, "--json delete Review").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["deleted"], id.to_string());
+        assert_eq!(json["number"], 2);
+        assert_eq!(
+            chronos(&mut tracker, "delete Review").unwrap_err(),
+            "no task named 'Review'"
+        );
+    }
+
+    #[test]
+    fn json_lists_tasks_with_their_time_in_seconds() {
+        let (mut tracker, clock) = tracker();
+        chronos(&mut tracker, "start Release > Notes @docs").unwrap();
+        clock.advance(Duration::from_secs(20 * 60));
+
+        let (_, out) = chronos(&mut tracker, "list --json").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        let tasks = json.as_array().unwrap();
+        assert_eq!(tasks.len(), 2);
+        let (release, notes) = (&tasks[0], &tasks[1]);
+        assert_eq!(release["name"], "Release");
+        assert_eq!(release["total_secs"], 20 * 60);
+        assert_eq!(release["time_spent_secs"], 0);
+        assert_eq!(notes["name"], "Notes");
+        assert_eq!(notes["status"], "Active");
+        assert_eq!(notes["tags"], serde_json::json!(["docs"]));
+        assert_eq!(notes["parent"], release["id"]);
+        assert!(!out.contains("Estimated"));
+    }
+
+    #[test]
+    fn interval_zero_is_rejected_instead_of_touching_the_first() {
+        let mut tracker = Tracker::default();
+        let i = tracker
+            .add_task(TaskSpec::parse("Write"), TaskStatus::Paused)
+            .unwrap();
+        let now = tracker.now();
+        let (start, end) = (now - chrono::Duration::hours(2), now);
+        tracker
+            .add_interval(tracker.tasks[i].id, start, end, None)
+            .unwrap();
+
+        let actions = [
+            IntervalCommand::Delete { number: 0 },
+            IntervalCommand::Edit {
+                number: 0,
+                start: Some("00:00".to_string()),
+                end: None,
+            },
+            IntervalCommand::Split {
+                number: 0,
+                at: "00:00".to_string(),
+            },
+        ];
+        for action in actions {
+            assert!(run_interval_command(&mut tracker, action).is_err());
+        }
+        assert_eq!(tracker.intervals.len(), 1);
+        assert_eq!(
+            (tracker.intervals[0].start, tracker.intervals[0].end),
+            (start, end)
+        );
+    }
+}
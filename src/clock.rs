@@ -0,0 +1,150 @@
+use chrono::{DateTime, Local, TimeDelta};
+
+use std::fmt::Debug;
+#[cfg(test)]
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Where the current time comes from. Everything that measures or stamps
+/// time asks the tracker's clock instead of calling `Local::now()`, so a
+/// test can stand still or skip ahead.
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+
+    /// Jumps ahead by `by`. Only simulated clocks can; the wall clock
+    /// refuses.
+    fn skip(&self, _by: Duration) -> Result<(), String> {
+        Err("the clock only skips in a --simulate session".to_string())
+    }
+}
+
+/// The wall clock.
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// The fastest `--simulate` clock, an hour every second. Much faster and a
+/// long session would run past the last date `DateTime` can hold.
+pub const MAX_SPEED: u32 = 3600;
+
+/// A clock for `--simulate`: it starts at the current time and runs
+/// `speed` times faster than the wall clock, plus whatever `skip` added.
+#[derive(Debug)]
+pub struct SimulatedClock {
+    origin: DateTime<Local>,
+    started: Instant,
+    speed: u32,
+    skipped: Mutex<Duration>,
+}
+
+impl SimulatedClock {
+    pub fn new(speed: u32) -> Self {
+        Self {
+            origin: Local::now(),
+            started: Instant::now(),
+            speed,
+            skipped: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// The simulated time with `skipped` added, `None` past the last date.
+    fn at(&self, skipped: Duration) -> Option<DateTime<Local>> {
+        let ran = self
+            .started
+            .elapsed()
+            .checked_mul(self.speed)?
+            .checked_add(skipped)?;
+        self.origin
+            .checked_add_signed(TimeDelta::from_std(ran).ok()?)
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> DateTime<Local> {
+        self.at(*self.skipped.lock().unwrap())
+            .expect("MAX_SPEED keeps the simulated time within range")
+    }
+
+    fn skip(&self, by: Duration) -> Result<(), String> {
+        let mut skipped = self.skipped.lock().unwrap();
+        *skipped = skipped
+            .checked_add(by)
+            .filter(|&total| self.at(total).is_some())
+            .ok_or("that skips past the last date there is")?;
+        Ok(())
+    }
+}
+
+/// A clock that only moves when told to. Clones share the same time, so a
+/// test can keep one and hand the other to the tracker.
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<DateTime<Local>>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new(start: DateTime<Local>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(start)),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Local> {
+        *self.now.lock().unwrap()
+    }
+
+    fn skip(&self, by: Duration) -> Result<(), String> {
+        self.advance(by);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_simulated_clock_runs_fast_and_skips_ahead() {
+        let clock = SimulatedClock::new(MAX_SPEED);
+        let start = clock.now();
+        std::thread::sleep(Duration::from_millis(20));
+        // 20ms at an hour a second is over a minute
+        assert!(clock.now() - start >= TimeDelta::minutes(1));
+
+        let before = clock.now();
+        clock.skip(Duration::from_secs(2 * 3600)).unwrap();
+        assert!(clock.now() - before >= TimeDelta::hours(2));
+    }
+
+    #[test]
+    fn skipping_past_the_last_date_is_refused() {
+        let clock = SimulatedClock::new(1);
+        assert_eq!(
+            clock.skip(Duration::from_secs(u64::MAX)),
+            Err("that skips past the last date there is".to_string())
+        );
+        assert!(
+            clock
+                .skip(Duration::from_secs(400_000 * 365 * 24 * 3600))
+                .is_err()
+        );
+        assert!(clock.now() - Local::now() < TimeDelta::minutes(1));
+        assert!(SystemClock.skip(Duration::from_secs(60)).is_err());
+    }
+}
@@ -0,0 +1,659 @@
+use crate::export::{self, Dataset, Format};
+use crate::history;
+use crate::report::Period;
+use crate::tracker::{TaskSpec, TaskStatus};
+use crate::util::{App, format_hms, parse_duration, parse_span, parse_when};
+
+use clap::ValueEnum;
+
+use std::time::Duration;
+
+/// Commands the `:` prompt understands, with the usage shown on errors.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("start", ":start <name> [~est] [+project] [@tag]"),
+    ("pause", ":pause [name]"),
+    ("resume", ":resume [name]"),
+    ("stop", ":stop [name]"),
+    ("rename", ":rename <new name>"),
+    ("tag", ":tag <tag>... (prefix with - to remove)"),
+    ("estimate", ":estimate <duration>"),
+    ("delete", ":delete"),
+    ("merge", ":merge <into task>"),
+    ("parent", ":parent [task] (none makes it top-level)"),
+    ("trash", ":trash"),
+    ("restore", ":restore <name>"),
+    ("undo", ":undo"),
+    ("redo", ":redo"),
+    ("add", ":add <duration> | [day] HH:MM-HH:MM"),
+    ("edit", ":edit <n> [day] HH:MM-HH:MM"),
+    ("split", ":split <n> [day] HH:MM"),
+    ("unlog", ":unlog <n>"),
+    ("report", ":report day|week|month"),
+    ("export", ":export csv|json [tasks|intervals|breaks|totals]"),
+    ("skip", ":skip <duration> (with --simulate)"),
+];
+
+/// A parsed `:` command. Commands without a task name act on the selected
+/// task.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Start(String),
+    Pause(Option<String>),
+    Resume(Option<String>),
+    Stop(Option<String>),
+    Rename(String),
+    Tag(Vec<String>),
+    Estimate(Duration),
+    Delete,
+    /// Fold the selected task into the named one.
+    Merge(String),
+    /// Make the selected task a subtask of the named one, or top-level.
+    Parent(Option<String>),
+    Trash,
+    Restore(String),
+    Undo,
+    Redo,
+    /// Past work on the selected task, as a duration ending now or a span.
+    Add(String),
+    Edit(usize, String),
+    Split(usize, String),
+    Unlog(usize),
+    Report(Period),
+    Export(Format, Option<Dataset>),
+    /// Move a simulated clock ahead.
+    Skip(Duration),
+}
+
+/// What the caller has to do once a command ran.
+pub enum Outcome {
+    Done(String),
+    ShowReports,
+}
+
+/// State of the COMMAND bar: the line being typed and the ones already run.
+#[derive(Default)]
+pub struct Prompt {
+    pub active: bool,
+    pub input: String,
+    pub history: Vec<String>,
+    /// Position in `history` while browsing it with Up/Down.
+    browsing: Option<usize>,
+    /// Last parse or run failure, shown in red until the prompt reopens.
+    pub error: Option<String>,
+}
+
+impl Prompt {
+    pub fn open(&mut self) {
+        self.active = true;
+        self.input.clear();
+        self.browsing = None;
+        self.error = None;
+    }
+
+    pub fn close(&mut self) {
+        self.active = false;
+        self.input.clear();
+        self.browsing = None;
+    }
+
+    /// Takes the typed line for running, remembering it in the history.
+    pub fn submit(&mut self) -> String {
+        let line = std::mem::take(&mut self.input);
+        if !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        self.close();
+        line
+    }
+
+    pub fn history_prev(&mut self) {
+        let pos = match self.browsing {
+            Some(0) => 0,
+            Some(p) => p - 1,
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+        self.browsing = Some(pos);
+        self.input = self.history[pos].clone();
+    }
+
+    pub fn history_next(&mut self) {
+        match self.browsing {
+            Some(p) if p + 1 < self.history.len() => {
+                self.browsing = Some(p + 1);
+                self.input = self.history[p + 1].clone();
+            }
+            Some(_) => {
+                self.browsing = None;
+                self.input.clear();
+            }
+            None => {}
+        }
+    }
+}
+
+pub fn parse(line: &str) -> Result<Command, String> {
+    let line = line.trim().trim_start_matches(':');
+    let (word, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim();
+    let arg = || (!rest.is_empty()).then(|| rest.to_string());
+
+    match word {
+        "start" if !rest.is_empty() => Ok(Command::Start(rest.to_string())),
+        "pause" => Ok(Command::Pause(arg())),
+        "resume" => Ok(Command::Resume(arg())),
+        "stop" => Ok(Command::Stop(arg())),
+        "rename" if !rest.is_empty() => Ok(Command::Rename(rest.to_string())),
+        "tag" if !rest.is_empty() => Ok(Command::Tag(
+            rest.split_whitespace()
+                .map(|t| t.trim_start_matches('@').to_string())
+                .collect(),
+        )),
+        "estimate" => parse_duration(rest)
+            .map(Command::Estimate)
+            .ok_or_else(|| format!("not a duration: '{rest}' (try 30m or 1h30m)")),
+        "delete" if rest.is_empty() => Ok(Command::Delete),
+        "merge" if !rest.is_empty() => Ok(Command::Merge(rest.to_string())),
+        "parent" => Ok(Command::Parent(arg())),
+        "trash" if rest.is_empty() => Ok(Command::Trash),
+        "restore" if !rest.is_empty() => Ok(Command::Restore(rest.to_string())),
+        "undo" if rest.is_empty() => Ok(Command::Undo),
+        "redo" if rest.is_empty() => Ok(Command::Redo),
+        "add" if !rest.is_empty() => Ok(Command::Add(rest.to_string())),
+        "edit" | "split" | "unlog" => {
+            let (n, when) = rest.split_once(' ').unwrap_or((rest, ""));
+            let n = n
+                .trim_start_matches('#')
+                .parse::<usize>()
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or_else(|| usage(word))?
+                - 1;
+            let when = when.trim().to_string();
+            match word {
+                "unlog" if when.is_empty() => Ok(Command::Unlog(n)),
+                "edit" if !when.is_empty() => Ok(Command::Edit(n, when)),
+                "split" if !when.is_empty() => Ok(Command::Split(n, when)),
+                _ => Err(usage(word)),
+            }
+        }
+        "report" => Period::from_str(rest, true)
+            .map(Command::Report)
+            .map_err(|_| usage("report")),
+        "export" => {
+            let mut args = rest.split_whitespace();
+            let format =
+                Format::from_str(args.next().unwrap_or(""), true).map_err(|_| usage("export"))?;
+            let dataset = args
+                .next()
+                .map(|d| Dataset::from_str(d, true).map_err(|_| usage("export")))
+                .transpose()?;
+            Ok(Command::Export(format, dataset))
+        }
+        "skip" => parse_duration(rest)
+            .map(Command::Skip)
+            .ok_or_else(|| usage("skip")),
+        "" => Err("type a command, <Tab> completes".to_string()),
+        _ if COMMANDS.iter().any(|(name, _)| *name == word) => Err(usage(word)),
+        _ => Err(format!(
+            "unknown command '{word}' (one of: {})",
+            COMMANDS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn usage(word: &str) -> String {
+    let usage = COMMANDS
+        .iter()
+        .find(|(name, _)| *name == word)
+        .map_or("", |(_, usage)| usage);
+    format!("usage: {usage}")
+}
+
+/// Runs a parsed command against `app`, returning a line of feedback.
+pub fn execute(app: &mut App, command: Command) -> Result<Outcome, String> {
+    let done = |msg: String| Ok(Outcome::Done(msg));
+
+    match command {
+        Command::Start(line) => {
+            let i = app.tracker.start(TaskSpec::parse(&line))?;
+            app.selected_index = Some(i);
+            done(format!("Started '{}'", app.tracker.tasks[i].name))
+        }
+        Command::Pause(name) => {
+            let i = target(app, name.as_deref())?;
+            if matches!(app.tracker.tasks[i].status, TaskStatus::Paused) {
+                return Err(format!("'{}' is not running", app.tracker.tasks[i].name));
+            }
+            app.tracker.pause(i, None);
+            done(format!(
+                "Paused '{}', break started",
+                app.tracker.tasks[i].name
+            ))
+        }
+        Command::Stop(name) => {
+            let i = target(app, name.as_deref())?;
+            if matches!(app.tracker.tasks[i].status, TaskStatus::Paused) {
+                return Err(format!("'{}' is not running", app.tracker.tasks[i].name));
+            }
+            app.tracker.stop(i, None);
+            done(format!("Stopped '{}'", app.tracker.tasks[i].name))
+        }
+        Command::Resume(name) => {
+            let i = target(app, name.as_deref())?;
+            if matches!(app.tracker.tasks[i].status, TaskStatus::Active) {
+                return Err(format!(
+                    "'{}' is already running",
+                    app.tracker.tasks[i].name
+                ));
+            }
+            app.tracker.resume(i);
+            done(format!("Resumed '{}'", app.tracker.tasks[i].name))
+        }
+        Command::Rename(name) => {
+            let i = target(app, None)?;
+            let old = app.tracker.tasks[i].name.clone();
+            app.tracker.rename(i, &name)?;
+            done(format!(
+                "Renamed '{old}' to '{}'",
+                app.tracker.tasks[i].name
+            ))
+        }
+        Command::Tag(tags) => {
+            let i = target(app, None)?;
+            let task = &mut app.tracker.tasks[i];
+            for tag in tags {
+                match tag.strip_prefix('-') {
+                    Some(removed) => task.tags.retain(|t| t != removed),
+                    None if !task.tags.contains(&tag) => task.tags.push(tag),
+                    None => {}
+                }
+            }
+            done(format!("Tags of '{}': {}", task.name, task.tags.join(", ")))
+        }
+        Command::Estimate(estimate) => {
+            let i = target(app, None)?;
+            app.tracker.tasks[i].expected_duration = estimate;
+            done(format!(
+                "Estimate of '{}' set to {}",
+                app.tracker.tasks[i].name,
+                format_hms(estimate)
+            ))
+        }
+        Command::Delete => {
+            let i = target(app, None)?;
+            let task = app.tracker.tasks[i].name.clone();
+            app.tracker.delete(i);
+            app.selected_index = match app.tracker.tasks.len() {
+                0 => None,
+                len => Some(i.min(len - 1)),
+            };
+            done(format!(
+                "Moved '{task}' to the trash, :restore brings it back"
+            ))
+        }
+        Command::Merge(into) => {
+            let from = target(app, None)?;
+            let into = target(app, Some(&into))?;
+            let name = app.tracker.tasks[from].name.clone();
+            let into = app.tracker.merge(from, into)?;
+            app.selected_index = Some(into);
+            done(format!(
+                "Merged '{name}' into '{}'",
+                app.tracker.tasks[into].name
+            ))
+        }
+        Command::Parent(name) => {
+            let i = target(app, None)?;
+            let parent = name.map(|n| target(app, Some(&n))).transpose()?;
+            app.tracker.set_parent(i, parent)?;
+            done(match parent {
+                Some(p) => format!(
+                    "'{}' is now under '{}'",
+                    app.tracker.tasks[i].name, app.tracker.tasks[p].name
+                ),
+                None => format!("'{}' is now a top-level task", app.tracker.tasks[i].name),
+            })
+        }
+        Command::Trash if app.tracker.trash.is_empty() => done("The trash is empty".to_string()),
+        Command::Trash => {
+            let names: Vec<&str> = app
+                .tracker
+                .trash
+                .iter()
+                .rev()
+                .map(|t| t.task.name.as_str())
+                .collect();
+            done(format!("Trash: {}", names.join(", ")))
+        }
+        Command::Restore(name) => {
+            let n = app
+                .tracker
+                .find_trashed(&name)
+                .ok_or_else(|| format!("no task named '{name}' in the trash"))?;
+            let i = app.tracker.restore(n)?;
+            app.selected_index = Some(i);
+            done(format!("Restored '{}'", app.tracker.tasks[i].name))
+        }
+        Command::Undo if history::undo(app) => done("Undid the last change".to_string()),
+        Command::Undo => Err("nothing to undo".to_string()),
+        Command::Redo if history::redo(app) => done("Redid the last undone change".to_string()),
+        Command::Redo => Err("nothing to redo".to_string()),
+        Command::Add(when) => {
+            let i = target(app, None)?;
+            let now = app.tracker.now();
+            let (start, end) = match parse_duration(&when) {
+                Some(d) => (now - d, now),
+                None => parse_span(&when, now.date_naive(), now.date_naive())
+                    .ok_or_else(|| format!("cannot read '{when}' as a duration or span"))?,
+            };
+            let task_id = app.tracker.tasks[i].id;
+            let n = app.tracker.add_interval(task_id, start, end, None)?;
+            done(with_overlaps(app, n, format!("Logged #{}", n + 1)))
+        }
+        Command::Edit(n, when) => {
+            let today = app.tracker.now().date_naive();
+            let day = app
+                .tracker
+                .intervals
+                .get(n)
+                .map_or(today, |iv| iv.start.date_naive());
+            let (start, end) = parse_span(&when, day, today)
+                .ok_or_else(|| format!("cannot read '{when}' as a span"))?;
+            let n = app.tracker.edit_interval(n, Some(start), Some(end))?;
+            done(with_overlaps(app, n, format!("Moved #{}", n + 1)))
+        }
+        Command::Split(n, when) => {
+            let today = app.tracker.now().date_naive();
+            let day = app
+                .tracker
+                .intervals
+                .get(n)
+                .map_or(today, |iv| iv.start.date_naive());
+            let at = parse_when(&when, day, today)
+                .ok_or_else(|| format!("cannot read '{when}' as a time"))?;
+            let second = app.tracker.split_interval(n, at)?;
+            done(format!(
+                "Split #{} into #{} and #{}",
+                n + 1,
+                n + 1,
+                second + 1
+            ))
+        }
+        Command::Unlog(n) => {
+            let removed = app.tracker.delete_interval(n)?;
+            done(format!(
+                "Removed #{} ({})",
+                n + 1,
+                format_hms(removed.duration())
+            ))
+        }
+        Command::Report(period) => {
+            app.report_period = period;
+            app.report_offset = 0;
+            Ok(Outcome::ShowReports)
+        }
+        Command::Export(format, dataset) => {
+            let dataset = dataset.unwrap_or(format.default_dataset());
+            let path = export::default_path(format, dataset, app.tracker.now());
+            export::render(&app.tracker, None, None, format, dataset)
+                .and_then(|contents| export::write(&path, &contents))
+                .map_err(|err| format!("export failed: {err}"))?;
+            done(format!("Exported to {}", path.display()))
+        }
+        Command::Skip(by) => {
+            app.tracker.clock.skip(by)?;
+            done(format!(
+                "Skipped {}, it is now {}",
+                format_hms(by),
+                app.tracker.now().format("%H:%M:%S")
+            ))
+        }
+    }
+}
+
+/// Appends a warning to `msg` when interval `n` overlaps others.
+fn with_overlaps(app: &App, n: usize, msg: String) -> String {
+    let overlaps = app.tracker.overlaps_with(n);
+    if overlaps.is_empty() {
+        return msg;
+    }
+    let others: Vec<String> = overlaps.iter().map(|m| format!("#{}", m + 1)).collect();
+    format!("{msg}, overlaps {}", others.join(", "))
+}
+
+/// The named task, or the selected one when no name was given.
+fn target(app: &App, name: Option<&str>) -> Result<usize, String> {
+    match name {
+        Some(name) => app
+            .tracker
+            .find(name)
+            .ok_or_else(|| format!("no task named '{name}'")),
+        None => app
+            .selected_index
+            .filter(|&i| i < app.tracker.tasks.len())
+            .ok_or_else(|| "no task selected".to_string()),
+    }
+}
+
+/// Completes the last word of the prompt: a command name first, then task
+/// names, tags, periods or export formats depending on the command.
+/// Returns the candidates left when the completion is ambiguous.
+pub fn complete(app: &mut App) -> Vec<String> {
+    let input = &app.prompt.input;
+    let (word, rest) = match input.split_once(' ') {
+        Some((word, rest)) => (word.to_string(), Some(rest.to_string())),
+        None => (input.clone(), None),
+    };
+
+    let (prefix, partial, candidates): (String, String, Vec<String>) = match rest {
+        None => (
+            String::new(),
+            word,
+            COMMANDS.iter().map(|(name, _)| name.to_string()).collect(),
+        ),
+        Some(rest) => {
+            let candidates: Vec<String> = match word.as_str() {
+                "start" | "pause" | "resume" | "stop" | "merge" | "parent" => {
+                    app.tracker.tasks.iter().map(|t| t.name.clone()).collect()
+                }
+                "restore" => app
+                    .tracker
+                    .trash
+                    .iter()
+                    .map(|t| t.task.name.clone())
+                    .collect(),
+                "tag" => {
+                    let mut tags: Vec<String> = app
+                        .tracker
+                        .tasks
+                        .iter()
+                        .flat_map(|t| t.tags.clone())
+                        .collect();
+                    tags.sort();
+                    tags.dedup();
+                    tags
+                }
+                "report" => ["day", "week", "month"].map(String::from).to_vec(),
+                "export" if !rest.contains(' ') => ["csv", "json"].map(String::from).to_vec(),
+                "export" => ["tasks", "intervals", "breaks", "totals"]
+                    .map(String::from)
+                    .to_vec(),
+                _ => Vec::new(),
+            };
+            // Task names may contain spaces, everything else completes one word
+            let split = match word.as_str() {
+                "start" | "pause" | "resume" | "stop" | "merge" | "parent" | "restore" => 0,
+                _ => rest.rfind(' ').map_or(0, |p| p + 1),
+            };
+            (
+                format!("{word} {}", &rest[..split]),
+                rest[split..].to_string(),
+                candidates,
+            )
+        }
+    };
+
+    let matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(&partial))
+        .collect();
+    match matches.as_slice() {
+        [] => Vec::new(),
+        [only] => {
+            app.prompt.input = format!("{prefix}{only} ");
+            Vec::new()
+        }
+        [first, others @ ..] => {
+            let common = others.iter().fold(first.clone(), |common, m| {
+                common
+                    .chars()
+                    .zip(m.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            });
+            app.prompt.input = format!("{prefix}{common}");
+            matches
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::tracker::Tracker;
+
+    use chrono::{Local, TimeZone};
+
+    use std::sync::Arc;
+
+    #[test]
+    fn parse_reads_arguments() {
+        assert_eq!(
+            parse(":start Write report ~30m"),
+            Ok(Command::Start("Write report ~30m".to_string()))
+        );
+        assert_eq!(parse("pause"), Ok(Command::Pause(None)));
+        assert_eq!(
+            parse("stop  Write "),
+            Ok(Command::Stop(Some("Write".to_string())))
+        );
+        assert_eq!(
+            parse("tag @a b"),
+            Ok(Command::Tag(vec!["a".to_string(), "b".to_string()]))
+        );
+        assert_eq!(
+            parse("estimate 1h30m"),
+            Ok(Command::Estimate(Duration::from_secs(90 * 60)))
+        );
+        assert_eq!(
+            parse("edit #2 9:00-10:00"),
+            Ok(Command::Edit(1, "9:00-10:00".to_string()))
+        );
+        assert_eq!(parse("unlog 3"), Ok(Command::Unlog(2)));
+        assert_eq!(parse("report WEEK"), Ok(Command::Report(Period::Week)));
+        assert_eq!(
+            parse("export csv totals"),
+            Ok(Command::Export(Format::Csv, Some(Dataset::Totals)))
+        );
+        assert_eq!(
+            parse("export json"),
+            Ok(Command::Export(Format::Json, None))
+        );
+    }
+
+    #[test]
+    fn parse_explains_what_is_wrong() {
+        assert_eq!(parse("unlog 0"), Err("usage: :unlog <n>".to_string()));
+        assert_eq!(parse("unlog 1 extra"), Err("usage: :unlog <n>".to_string()));
+        assert_eq!(
+            parse("split 2"),
+            Err("usage: :split <n> [day] HH:MM".to_string())
+        );
+        assert_eq!(parse("start"), Err(usage("start")));
+        assert_eq!(parse("export xml"), Err(usage("export")));
+        assert!(
+            parse("estimate soon")
+                .unwrap_err()
+                .contains("not a duration")
+        );
+        assert!(
+            parse("frobnicate")
+                .unwrap_err()
+                .starts_with("unknown command 'frobnicate'")
+        );
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn skip_moves_only_a_clock_that_can_skip() {
+        let run = |app: &mut App, line| match parse(line).and_then(|cmd| execute(app, cmd)) {
+            Ok(Outcome::Done(msg)) => Ok(msg),
+            Ok(Outcome::ShowReports) => Err("switched views".to_string()),
+            Err(err) => Err(err),
+        };
+        let start = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(MockClock::new(start));
+        let mut app = App::new(tracker);
+        assert_eq!(
+            run(&mut app, "skip 1h30m"),
+            Ok("Skipped 01:30:00, it is now 10:30:00".to_string())
+        );
+        assert_eq!(app.tracker.now() - start, chrono::TimeDelta::minutes(90));
+
+        let mut app = App::new(Tracker::default());
+        assert_eq!(
+            run(&mut app, "skip 5m"),
+            Err("the clock only skips in a --simulate session".to_string())
+        );
+    }
+
+    fn complete_after(app: &mut App, input: &str) -> Vec<String> {
+        app.prompt.input = input.to_string();
+        complete(app)
+    }
+
+    #[test]
+    fn complete_fills_in_commands_and_arguments() {
+        let mut tracker = Tracker::default();
+        for name in ["Write report @docs", "Write tests @code", "Email"] {
+            tracker
+                .add_task(TaskSpec::parse(name), TaskStatus::Paused)
+                .unwrap();
+        }
+        let mut app = App::new(tracker);
+
+        assert!(complete_after(&mut app, "sta").is_empty());
+        assert_eq!(app.prompt.input, "start ");
+
+        // Ambiguous: fills the common part and lists the candidates
+        assert_eq!(
+            complete_after(&mut app, "s"),
+            vec!["start", "stop", "split", "skip"]
+        );
+        assert_eq!(app.prompt.input, "s");
+        assert_eq!(
+            complete_after(&mut app, "resume Wr"),
+            vec!["Write report", "Write tests"]
+        );
+        assert_eq!(app.prompt.input, "resume Write ");
+
+        // Task names complete whole, spaces and all
+        complete_after(&mut app, "merge Write t");
+        assert_eq!(app.prompt.input, "merge Write tests ");
+
+        complete_after(&mut app, "tag urgent co");
+        assert_eq!(app.prompt.input, "tag urgent code ");
+        complete_after(&mut app, "export csv br");
+        assert_eq!(app.prompt.input, "export csv breaks ");
+        assert!(complete_after(&mut app, "unlog 1").is_empty());
+        assert_eq!(app.prompt.input, "unlog 1");
+    }
+}
@@ -0,0 +1,515 @@
+//! Settings read from `$XDG_CONFIG_HOME/chronos/config.toml`, or the file
+//! given with `--config`. Every field has a default, so the file only needs
+//! the values someone wants to change:
+//!
+//! ```toml
+//! [tracking]
+//! default_estimate_minutes = 90
+//!
+//! [ui]
+//! chart_max_minutes = 60
+//!
+//! [layout]
+//! body = 75
+//! footer = 5
+//!
+//! [colors]
+//! text = "white"
+//! heading = "#ff8800"
+//! ```
+//!
+//! Colors are names (`yellow`, `dark-gray`), `#rrggbb` or a palette index.
+//! Layout values are percentages of the screen height for the four rows,
+//! which have to add up to 100, and of the body width for the side panels.
+//! The Settings view keeps the rows at 100 by giving the body whatever the
+//! header, nav and footer leave.
+
+use crate::keymap::{Action, Keymap, Preset};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub pomodoro: PomodoroConfig,
+    pub idle: IdleConfig,
+    pub tracking: TrackingConfig,
+    pub keys: KeysConfig,
+    pub ui: UiConfig,
+    pub layout: LayoutConfig,
+    pub colors: ColorsConfig,
+}
+
+/// Length of the work/break phases, in minutes. The default is the classic
+/// 25/5 repeated four times, with a 15 minute break closing the cycle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PomodoroConfig {
+    pub work_minutes: u64,
+    pub short_break_minutes: u64,
+    pub long_break_minutes: u64,
+    /// Number of work phases per cycle; the last break of a cycle is long.
+    pub long_break_every: usize,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+        }
+    }
+}
+
+/// When the TUI decides nobody was at the keyboard while a task kept running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdleConfig {
+    pub enabled: bool,
+    /// Minutes without a key press before asking what the time was.
+    pub idle_minutes: u64,
+    /// A gap between two ticks at least this long means the machine slept.
+    pub suspend_minutes: u64,
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_minutes: 10,
+            suspend_minutes: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrackingConfig {
+    /// Starting or resuming a task pauses whichever one was running, so time
+    /// is never counted twice. Turn off to track tasks in parallel.
+    pub single_active: bool,
+    /// Estimate of a task added without a `~<duration>`.
+    pub default_estimate_minutes: u64,
+}
+
+impl Default for TrackingConfig {
+    fn default() -> Self {
+        Self {
+            single_active: true,
+            default_estimate_minutes: 120,
+        }
+    }
+}
+
+/// Key bindings: a preset plus per-action overrides, see `keymap`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: Preset,
+    /// Keys replacing the preset's for an action, e.g. `pause = ["ctrl+p"]`.
+    pub bindings: BTreeMap<Action, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Top of the scale of the time charts; longer bars are cut off.
+    pub chart_max_minutes: u64,
+    /// How long the banner waits for a key before drawing again.
+    pub banner_wait_ms: u64,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            chart_max_minutes: 20,
+            banner_wait_ms: 100,
+        }
+    }
+}
+
+/// How the screen is split, in percent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// The title bar.
+    pub header: u16,
+    /// The textarea and the MODES row.
+    pub nav: u16,
+    /// The current mode's tables and panels.
+    pub body: u16,
+    /// The MENU row.
+    pub footer: u16,
+    /// Width of the STATUS panel next to the TASKS table.
+    pub status_panel: u16,
+    /// Width of the logs next to the Timer view's table.
+    pub logs_panel: u16,
+    /// Width of the chart next to the report table.
+    pub chart_panel: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            header: 10,
+            nav: 10,
+            body: 70,
+            footer: 10,
+            status_panel: 30,
+            logs_panel: 50,
+            chart_panel: 40,
+        }
+    }
+}
+
+/// The palette every view draws with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorsConfig {
+    #[serde(with = "color")]
+    pub background: Color,
+    /// Borders and most of the text.
+    #[serde(with = "color")]
+    pub text: Color,
+    /// Titles, table headers and key hints.
+    #[serde(with = "color")]
+    pub heading: Color,
+    /// The selected row, chart bars and things that want attention.
+    #[serde(with = "color")]
+    pub highlight: Color,
+    /// Over budget, overlong breaks and errors.
+    #[serde(with = "color")]
+    pub alert: Color,
+    /// Running tasks and confirmations.
+    #[serde(with = "color")]
+    pub success: Color,
+    /// Details in the side panels.
+    #[serde(with = "color")]
+    pub info: Color,
+    /// Chart labels and the header line.
+    #[serde(with = "color")]
+    pub label: Color,
+    /// Hints and whatever is idle.
+    #[serde(with = "color")]
+    pub muted: Color,
+    /// The filled part of progress bars.
+    #[serde(with = "color")]
+    pub progress: Color,
+}
+
+impl Default for ColorsConfig {
+    fn default() -> Self {
+        Self {
+            background: Color::Rgb(10, 14, 32),
+            text: Color::Rgb(0, 200, 180),
+            heading: Color::Rgb(255, 165, 0),
+            highlight: Color::Yellow,
+            alert: Color::Red,
+            success: Color::Rgb(0, 200, 83),
+            info: Color::Rgb(173, 216, 230),
+            label: Color::Rgb(102, 217, 239),
+            muted: Color::DarkGray,
+            progress: Color::Rgb(30, 102, 245),
+        }
+    }
+}
+
+/// Colors are written the way ratatui reads them.
+mod color {
+    use ratatui::style::Color;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_string().to_lowercase())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Color::from_str(&text).map_err(|_| {
+            D::Error::custom(format!(
+                "'{text}' is not a color; use a name like \"yellow\", \"#rrggbb\" or a palette index"
+            ))
+        })
+    }
+}
+
+impl Config {
+    /// Checks the values the types alone cannot, naming the setting that is
+    /// off and what it has to be.
+    pub fn validate(&self) -> Result<(), String> {
+        let p = &self.pomodoro;
+        minutes("pomodoro.work_minutes", p.work_minutes)?;
+        minutes("pomodoro.short_break_minutes", p.short_break_minutes)?;
+        minutes("pomodoro.long_break_minutes", p.long_break_minutes)?;
+        at_least("pomodoro.long_break_every", p.long_break_every as u64, 1)?;
+        if p.long_break_every > MAX_LONG_BREAK_EVERY {
+            return Err(format!(
+                "pomodoro.long_break_every must be at most {MAX_LONG_BREAK_EVERY}, got {}",
+                p.long_break_every
+            ));
+        }
+
+        minutes("idle.idle_minutes", self.idle.idle_minutes)?;
+        minutes("idle.suspend_minutes", self.idle.suspend_minutes)?;
+
+        minutes(
+            "tracking.default_estimate_minutes",
+            self.tracking.default_estimate_minutes,
+        )?;
+        minutes("ui.chart_max_minutes", self.ui.chart_max_minutes)?;
+        at_least("ui.banner_wait_ms", self.ui.banner_wait_ms, 10)?;
+
+        let l = &self.layout;
+        for (key, value) in [
+            ("layout.header", l.header),
+            ("layout.nav", l.nav),
+            ("layout.body", l.body),
+            ("layout.footer", l.footer),
+        ] {
+            // Each row leaves at least 1% for the other three
+            if !(1..=97).contains(&value) {
+                return Err(format!("{key} must be between 1 and 97, got {value}"));
+            }
+        }
+        let rows = [l.header, l.nav, l.body, l.footer]
+            .into_iter()
+            .map(u32::from)
+            .sum::<u32>();
+        if rows != 100 {
+            return Err(format!(
+                "layout.header, nav, body and footer must add up to 100, they add up to {rows}"
+            ));
+        }
+        for (key, value) in [
+            ("layout.status_panel", l.status_panel),
+            ("layout.logs_panel", l.logs_panel),
+            ("layout.chart_panel", l.chart_panel),
+        ] {
+            if !(10..=90).contains(&value) {
+                return Err(format!("{key} must be between 10 and 90, got {value}"));
+            }
+        }
+
+        Keymap::new(&self.keys)?;
+        Ok(())
+    }
+
+    /// The settings as `section.key` with their values written as TOML, the
+    /// way the Settings view lists them.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let Ok(toml::Value::Table(sections)) = toml::Value::try_from(self) else {
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        for (section, table) in sections {
+            let Some(table) = table.as_table() else {
+                continue;
+            };
+            for (key, value) in table {
+                entries.push((format!("{section}.{key}"), value.to_string()));
+            }
+        }
+        entries
+    }
+
+    /// A copy with the setting `section.key` set to `input`, read as a TOML
+    /// value. Anything that is not valid TOML is taken as a string, so
+    /// `vi` or `#00c8b4` need no quotes. Changing a layout row resizes the
+    /// body to match.
+    pub fn with(&self, key: &str, input: &str) -> Result<Config, String> {
+        let (section, name) = key
+            .split_once('.')
+            .ok_or(format!("'{key}' is not a setting"))?;
+        let value = toml::from_str::<toml::Table>(&format!("value = {input}"))
+            .ok()
+            .and_then(|mut t| t.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(input.trim().to_string()));
+
+        let mut config = toml::Value::try_from(self).map_err(|err| err.to_string())?;
+        let Some(table) = config.get_mut(section).and_then(|t| t.as_table_mut()) else {
+            return Err(format!("'{key}' is not a setting"));
+        };
+        if !table.contains_key(name) {
+            return Err(format!("'{key}' is not a setting"));
+        }
+        if key == "layout.body" {
+            return Err(
+                "layout.body is what header, nav and footer leave; change one of those".into(),
+            );
+        }
+        table.insert(name.to_string(), value);
+
+        let mut config: Config = config
+            .try_into()
+            .map_err(|err: toml::de::Error| format!("{key}: {}", err.message()))?;
+        if matches!(key, "layout.header" | "layout.nav" | "layout.footer") {
+            let l = &mut config.layout;
+            let rows = u32::from(l.header) + u32::from(l.nav) + u32::from(l.footer);
+            if rows > 99 {
+                return Err(format!(
+                    "{key}: header, nav and footer must leave at least 1 for the body, they take {rows}"
+                ));
+            }
+            l.body = 100 - rows as u16;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// The most any `*_minutes` setting can be, about two years. Anything
+/// above it is a typo, and it keeps the settings clear of overflow once
+/// they are turned into durations.
+const MAX_MINUTES: u64 = 1_000_000;
+
+/// The most work phases a pomodoro cycle can have before its long break.
+const MAX_LONG_BREAK_EVERY: usize = 100;
+
+fn at_least(key: &str, value: u64, min: u64) -> Result<(), String> {
+    if value < min {
+        return Err(format!("{key} must be at least {min}, got {value}"));
+    }
+    Ok(())
+}
+
+fn minutes(key: &str, value: u64) -> Result<(), String> {
+    at_least(key, value, 1)?;
+    if value > MAX_MINUTES {
+        return Err(format!("{key} must be at most {MAX_MINUTES}, got {value}"));
+    }
+    Ok(())
+}
+
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("chronos")
+        .join("config.toml")
+}
+
+/// Reads the config file, falling back to the defaults when it is missing.
+pub fn load(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+
+    let config: Config = toml::from_str(&fs::read_to_string(path)?)
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    config
+        .validate()
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    Ok(config)
+}
+
+/// Writes `config` to `path` with every setting spelled out. Comments in
+/// the old file do not survive.
+pub fn save(path: &Path, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, toml::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_defaults_survive_a_round_trip() {
+        let text = toml::to_string_pretty(&Config::default()).unwrap();
+        let config: Config = toml::from_str(&text).unwrap();
+        assert_eq!(config.entries(), Config::default().entries());
+        assert_eq!(config.colors.text, Color::Rgb(0, 200, 180));
+        assert_eq!(config.colors.muted, Color::DarkGray);
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn errors_name_the_setting() {
+        let err = toml::from_str::<Config>("[colors]\ntext = \"teal-ish\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'teal-ish' is not a color"), "{err}");
+
+        let mut config = Config::default();
+        config.layout.footer = 15;
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "layout.header, nav, body and footer must add up to 100, they add up to 105"
+        );
+        config = Config::default();
+        config.layout.body = u16::MAX;
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "layout.body must be between 1 and 97, got 65535"
+        );
+        config = Config::default();
+        config.pomodoro.long_break_every = 4_000_000_000_000;
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "pomodoro.long_break_every must be at most 100, got 4000000000000"
+        );
+        config = Config::default();
+        config.idle.suspend_minutes = u64::MAX;
+        assert_eq!(
+            config.validate().unwrap_err(),
+            format!(
+                "idle.suspend_minutes must be at most 1000000, got {}",
+                u64::MAX
+            )
+        );
+        config = Config::default();
+        config.pomodoro.work_minutes = 0;
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "pomodoro.work_minutes must be at least 1, got 0"
+        );
+    }
+
+    #[test]
+    fn with_sets_one_setting_and_validates_the_result() {
+        let config = Config::default();
+        let changed = config.with("pomodoro.work_minutes", "50").unwrap();
+        assert_eq!(changed.pomodoro.work_minutes, 50);
+        let changed = config.with("colors.heading", "#ff8800").unwrap();
+        assert_eq!(changed.colors.heading, Color::Rgb(255, 136, 0));
+        let changed = config.with("keys.preset", "vi").unwrap();
+        assert_eq!(changed.keys.preset, Preset::Vi);
+
+        assert!(config.with("pomodoro.work_minutes", "soon").is_err());
+        assert_eq!(
+            config.with("ui.chart_max_minutes", "0").unwrap_err(),
+            "ui.chart_max_minutes must be at least 1, got 0"
+        );
+        assert_eq!(
+            config.with("ui.colour", "red").unwrap_err(),
+            "'ui.colour' is not a setting"
+        );
+    }
+
+    #[test]
+    fn a_layout_row_takes_its_room_from_the_body() {
+        let config = Config::default();
+        let changed = config.with("layout.footer", "5").unwrap();
+        assert_eq!((changed.layout.footer, changed.layout.body), (5, 75));
+        let changed = changed.with("layout.header", "20").unwrap();
+        assert_eq!((changed.layout.header, changed.layout.body), (20, 65));
+
+        assert_eq!(
+            config.with("layout.nav", "85").unwrap_err(),
+            "layout.nav: header, nav and footer must leave at least 1 for the body, they take 105"
+        );
+        assert!(config.with("layout.body", "60").is_err());
+    }
+}
@@ -0,0 +1,497 @@
+//! CSV and JSON export of tracked time.
+//!
+//! The column names below are a stable interface for spreadsheets and
+//! billing scripts: columns are only ever appended, never renamed or
+//! reordered. JSON uses the same field names, one array per dataset.
+//! Timestamps are RFC 3339 in local time, durations are whole seconds and
+//! tags are joined with `;`.
+//!
+//! `tasks`:     task_id, name, project, tags, status, estimate_secs, time_spent_secs,
+//!              parent_id, task_uuid
+//! `intervals`: task_id, task_name, project, tags, start, end, duration_secs, note,
+//!              task_uuid
+//! `breaks`:    task_id, task_name, ended_at, planned_secs, actual_secs, overrun_secs,
+//!              task_uuid
+//! `totals`:    date, task_name, duration_secs
+//!
+//! The date range keeps intervals by the day they started and breaks by the
+//! day they ended. `time_spent_secs` on a task is the sum of its exported
+//! intervals, so running stretches are left out until they are closed, and
+//! never includes subtasks; `parent_id` is empty for top-level tasks.
+//! `task_id` and `parent_id` are the numbers shown in the app, `task_uuid`
+//! is the id that stays the same across machines.
+
+use crate::report::{self, Breakdown, Period};
+use crate::tracker::{TaskId, Tracker};
+
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+
+    /// Dataset used when none is asked for: the interval log for CSV and
+    /// everything for JSON.
+    pub fn default_dataset(self) -> Dataset {
+        match self {
+            Format::Csv => Dataset::Intervals,
+            Format::Json => Dataset::All,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Dataset {
+    All,
+    Tasks,
+    Intervals,
+    Breaks,
+    Totals,
+}
+
+#[derive(Serialize)]
+struct TaskRow {
+    task_id: u32,
+    name: String,
+    project: String,
+    tags: String,
+    status: String,
+    estimate_secs: u64,
+    time_spent_secs: u64,
+    parent_id: Option<u32>,
+    task_uuid: TaskId,
+}
+
+#[derive(Serialize)]
+struct IntervalRow {
+    task_id: u32,
+    task_name: String,
+    project: String,
+    tags: String,
+    start: String,
+    end: String,
+    duration_secs: u64,
+    note: String,
+    task_uuid: TaskId,
+}
+
+#[derive(Serialize)]
+struct BreakRow {
+    task_id: u32,
+    task_name: String,
+    ended_at: String,
+    planned_secs: u64,
+    actual_secs: u64,
+    overrun_secs: u64,
+    task_uuid: TaskId,
+}
+
+#[derive(Serialize)]
+struct TotalRow {
+    date: String,
+    task_name: String,
+    duration_secs: u64,
+}
+
+#[derive(Serialize)]
+struct Export {
+    tasks: Vec<TaskRow>,
+    intervals: Vec<IntervalRow>,
+    breaks: Vec<BreakRow>,
+    totals: Vec<TotalRow>,
+}
+
+/// Renders `dataset` between `from` and `to` (inclusive, open-ended when
+/// `None`). CSV holds a single dataset, so `All` is rejected there.
+pub fn render(
+    tracker: &Tracker,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    format: Format,
+    dataset: Dataset,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let export = collect(tracker, from, to);
+
+    if format == Format::Json {
+        let json = match dataset {
+            Dataset::All => serde_json::to_string_pretty(&export)?,
+            Dataset::Tasks => serde_json::to_string_pretty(&export.tasks)?,
+            Dataset::Intervals => serde_json::to_string_pretty(&export.intervals)?,
+            Dataset::Breaks => serde_json::to_string_pretty(&export.breaks)?,
+            Dataset::Totals => serde_json::to_string_pretty(&export.totals)?,
+        };
+        return Ok(json);
+    }
+
+    let (header, records): (&[&str], Vec<Vec<String>>) = match dataset {
+        Dataset::All => {
+            return Err(
+                "CSV export needs a single dataset: tasks, intervals, breaks or totals".into(),
+            );
+        }
+        Dataset::Tasks => (
+            &[
+                "task_id",
+                "name",
+                "project",
+                "tags",
+                "status",
+                "estimate_secs",
+                "time_spent_secs",
+                "parent_id",
+                "task_uuid",
+            ],
+            export
+                .tasks
+                .into_iter()
+                .map(|r| {
+                    vec![
+                        r.task_id.to_string(),
+                        r.name,
+                        r.project,
+                        r.tags,
+                        r.status,
+                        r.estimate_secs.to_string(),
+                        r.time_spent_secs.to_string(),
+                        r.parent_id.map(|p| p.to_string()).unwrap_or_default(),
+                        r.task_uuid.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        Dataset::Intervals => (
+            &[
+                "task_id",
+                "task_name",
+                "project",
+                "tags",
+                "start",
+                "end",
+                "duration_secs",
+                "note",
+                "task_uuid",
+            ],
+            export
+                .intervals
+                .into_iter()
+                .map(|r| {
+                    vec![
+                        r.task_id.to_string(),
+                        r.task_name,
+                        r.project,
+                        r.tags,
+                        r.start,
+                        r.end,
+                        r.duration_secs.to_string(),
+                        r.note,
+                        r.task_uuid.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        Dataset::Breaks => (
+            &[
+                "task_id",
+                "task_name",
+                "ended_at",
+                "planned_secs",
+                "actual_secs",
+                "overrun_secs",
+                "task_uuid",
+            ],
+            export
+                .breaks
+                .into_iter()
+                .map(|r| {
+                    vec![
+                        r.task_id.to_string(),
+                        r.task_name,
+                        r.ended_at,
+                        r.planned_secs.to_string(),
+                        r.actual_secs.to_string(),
+                        r.overrun_secs.to_string(),
+                        r.task_uuid.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        Dataset::Totals => (
+            &["date", "task_name", "duration_secs"],
+            export
+                .totals
+                .into_iter()
+                .map(|r| vec![r.date, r.task_name, r.duration_secs.to_string()])
+                .collect(),
+        ),
+    };
+
+    let mut out = header.join(",");
+    out.push('\n');
+    for record in records {
+        let fields: Vec<String> = record.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Where the TUI writes exports: `$XDG_DATA_HOME/chronos/exports/`, named
+/// after the time `now` on the tracker's clock.
+pub fn default_path(format: Format, dataset: Dataset, now: DateTime<Local>) -> PathBuf {
+    let dataset = format!("{dataset:?}").to_lowercase();
+    crate::storage::data_path()
+        .with_file_name("exports")
+        .join(format!(
+            "chronos-{dataset}-{}.{}",
+            now.format("%Y%m%d-%H%M%S"),
+            format.extension()
+        ))
+}
+
+/// Writes an export to `path`, creating its directory if needed.
+pub fn write(path: &Path, contents: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+fn collect(tracker: &Tracker, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Export {
+    let in_range = |day: NaiveDate| from.is_none_or(|f| day >= f) && to.is_none_or(|t| day <= t);
+
+    let intervals: Vec<IntervalRow> = tracker
+        .intervals
+        .iter()
+        .filter(|iv| in_range(iv.start.date_naive()))
+        .filter_map(|iv| {
+            let task = tracker.tasks.iter().find(|t| t.id == iv.task_id)?;
+            Some(IntervalRow {
+                task_id: task.number,
+                task_name: task.name.clone(),
+                project: task.project.clone().unwrap_or_default(),
+                tags: task.tags.join(";"),
+                start: iv.start.to_rfc3339_opts(SecondsFormat::Secs, false),
+                end: iv.end.to_rfc3339_opts(SecondsFormat::Secs, false),
+                duration_secs: iv.duration().as_secs(),
+                note: iv.note.clone().unwrap_or_default(),
+                task_uuid: task.id,
+            })
+        })
+        .collect();
+
+    let tasks = tracker
+        .tasks
+        .iter()
+        .map(|t| TaskRow {
+            task_id: t.number,
+            name: t.name.clone(),
+            project: t.project.clone().unwrap_or_default(),
+            tags: t.tags.join(";"),
+            status: format!("{:?}", t.status),
+            estimate_secs: t.expected_duration.as_secs(),
+            time_spent_secs: intervals
+                .iter()
+                .filter(|r| r.task_uuid == t.id)
+                .map(|r| r.duration_secs)
+                .sum(),
+            parent_id: t
+                .parent
+                .and_then(|p| tracker.tasks.iter().find(|t| t.id == p))
+                .map(|p| p.number),
+            task_uuid: t.id,
+        })
+        .collect();
+
+    let breaks = tracker
+        .tasks
+        .iter()
+        .filter_map(|t| Some((t, tracker.breaks.get(&t.id)?)))
+        .flat_map(|(t, b)| {
+            b.history
+                .iter()
+                .filter(|r| in_range(r.ended_at.date_naive()))
+                .map(|r| BreakRow {
+                    task_id: t.number,
+                    task_name: t.name.clone(),
+                    ended_at: r.ended_at.to_rfc3339_opts(SecondsFormat::Secs, false),
+                    planned_secs: r.planned.as_secs(),
+                    actual_secs: r.actual.as_secs(),
+                    overrun_secs: r.overrun().as_secs(),
+                    task_uuid: t.id,
+                })
+        })
+        .collect();
+
+    // Daily totals go through the report so they split at midnight the same way
+    let today = tracker.now().date_naive();
+    let first_day = tracker
+        .intervals
+        .iter()
+        .map(|iv| iv.start.date_naive())
+        .min()
+        .unwrap_or(today);
+    let totals = report::build(
+        tracker,
+        from.unwrap_or(first_day),
+        to.unwrap_or(today),
+        Period::Day,
+        Breakdown::Task,
+    )
+    .into_iter()
+    .flat_map(|day| {
+        day.entries
+            .into_iter()
+            .map(move |(task_name, spent)| TotalRow {
+                date: day.period.clone(),
+                task_name,
+                duration_secs: spent.as_secs(),
+            })
+    })
+    .collect();
+
+    Export {
+        tasks,
+        intervals,
+        breaks,
+        totals,
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::tracker::{TaskSpec, TaskStatus};
+
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    /// "Write +docs @a @b" under "Release", with one interval noted
+    /// `drafted "intro", outline`.
+    fn tracker() -> Tracker {
+        let at = |h| Local.with_ymd_and_hms(2026, 2, 2, h, 0, 0).unwrap();
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(MockClock::new(at(12)));
+        let i = tracker
+            .add_task(
+                TaskSpec::parse("Release > Write +docs @a @b ~1h"),
+                TaskStatus::Paused,
+            )
+            .unwrap();
+        let note = "drafted \"intro\", outline".to_string();
+        let id = tracker.tasks[i].id;
+        tracker.add_interval(id, at(9), at(10), Some(note)).unwrap();
+        tracker
+    }
+
+    fn csv(tracker: &Tracker, dataset: Dataset) -> Vec<String> {
+        render(tracker, None, None, Format::Csv, dataset)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn the_default_path_is_named_after_the_trackers_time() {
+        let tracker = tracker();
+        let path = default_path(Format::Csv, Dataset::Totals, tracker.now());
+        assert_eq!(
+            path.file_name().unwrap(),
+            "chronos-totals-20260202-120000.csv"
+        );
+        assert_eq!(path.parent().unwrap().file_name().unwrap(), "exports");
+    }
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_columns_keep_their_documented_order() {
+        let tracker = tracker();
+        let uuid = tracker.tasks[1].id;
+        let tasks = csv(&tracker, Dataset::Tasks);
+        assert_eq!(
+            tasks[0],
+            "task_id,name,project,tags,status,estimate_secs,time_spent_secs,parent_id,task_uuid"
+        );
+        assert_eq!(
+            tasks[2],
+            format!("2,Write,docs,a;b,Paused,3600,3600,1,{uuid}")
+        );
+
+        let intervals = csv(&tracker, Dataset::Intervals);
+        assert_eq!(
+            intervals[0],
+            "task_id,task_name,project,tags,start,end,duration_secs,note,task_uuid"
+        );
+        let start = tracker.intervals[0]
+            .start
+            .to_rfc3339_opts(SecondsFormat::Secs, false);
+        let end = tracker.intervals[0]
+            .end
+            .to_rfc3339_opts(SecondsFormat::Secs, false);
+        assert_eq!(
+            intervals[1],
+            format!(
+                "2,Write,docs,a;b,{start},{end},3600,\"drafted \"\"intro\"\", outline\",{uuid}"
+            )
+        );
+
+        assert_eq!(
+            csv(&tracker, Dataset::Breaks)[0],
+            "task_id,task_name,ended_at,planned_secs,actual_secs,overrun_secs,task_uuid"
+        );
+        assert_eq!(
+            csv(&tracker, Dataset::Totals),
+            vec!["date,task_name,duration_secs", "2026-02-02,Write,3600"]
+        );
+        assert!(render(&tracker, None, None, Format::Csv, Dataset::All).is_err());
+    }
+
+    #[test]
+    fn json_uses_the_csv_column_names() {
+        let tracker = tracker();
+        let json = render(&tracker, None, None, Format::Json, Dataset::Intervals).unwrap();
+        let rows: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let keys: Vec<&str> = rows[0]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let header = &csv(&tracker, Dataset::Intervals)[0];
+        let mut columns: Vec<&str> = header.split(',').collect();
+        columns.sort_unstable();
+        assert_eq!(keys, columns);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::idle_ui;
+use crate::redundancy_warning::centered_rect;
+use crate::tui::Mode;
+use crate::util::App;
+
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Padding, Paragraph},
+};
+
+/// Draws every binding of the active keymap, the current mode's first.
+pub fn pop_up(frame: &mut Frame, app: &App, mode: Mode) {
+    if !app.show_help {
+        return;
+    }
+    let colors = app.config.colors;
+
+    let area = centered_rect(70, 80, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title(format!("KEYS ({:?} preset)", app.keymap.preset).to_uppercase())
+        .title_bottom(Line::from(" any key closes ").right_aligned())
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .padding(Padding::new(2, 2, 1, 1))
+        .style(Style::default().bg(colors.background).fg(colors.text));
+
+    let mut order = vec![mode];
+    order.extend(Mode::ALL.into_iter().filter(|&m| m != mode));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    let columns =
+        Layout::horizontal([Constraint::Ratio(1, Mode::ALL.len() as u32); Mode::ALL.len()])
+            .split(inner);
+    let section = |title: &str, actions| {
+        let mut lines =
+            vec![Line::from(title.to_uppercase()).style(Style::new().fg(colors.heading).bold())];
+        for (keys, label) in app.keymap.help(actions) {
+            lines.push(Line::from(vec![
+                Span::raw(format!("{keys:<16} ")).style(Style::new().fg(colors.highlight)),
+                Span::raw(label),
+            ]));
+        }
+        lines
+    };
+    let mut sections: Vec<Vec<Line>> = order
+        .into_iter()
+        .map(|m| section(m.title(), m.actions()))
+        .collect();
+
+    // The away prompt works over every mode, so it goes where there is room
+    if let Some(shortest) = sections.iter_mut().min_by_key(|lines| lines.len()) {
+        shortest.push(Line::from(""));
+        shortest.extend(section("Away prompt", idle_ui::ACTIONS));
+    }
+    for (lines, column) in sections.into_iter().zip(columns.iter()) {
+        frame.render_widget(Paragraph::new(lines), *column);
+    }
+}
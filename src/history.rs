@@ -0,0 +1,246 @@
+use crate::tracker::{Break, Interval, Task, TaskId, Trashed};
+use crate::util::App;
+
+use std::collections::HashMap;
+
+/// Most undo steps kept; older ones are dropped.
+const DEPTH: usize = 100;
+
+/// Everything undo/redo puts back: the tracked data, not view state.
+#[derive(Clone, PartialEq)]
+pub struct Snapshot {
+    next_number: u32,
+    tasks: Vec<Task>,
+    intervals: Vec<Interval>,
+    breaks: HashMap<TaskId, Break>,
+    trash: Vec<Trashed>,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// State before the input that is being handled right now.
+    pending: Option<Snapshot>,
+    /// Counts the changes recorded, undone and redone, so `Autosave` can
+    /// tell whether there is anything new to write.
+    version: u64,
+}
+
+impl History {
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+fn snapshot(app: &App) -> Snapshot {
+    let tracker = &app.tracker;
+    Snapshot {
+        next_number: tracker.next_number,
+        tasks: tracker.tasks.clone(),
+        intervals: tracker.intervals.clone(),
+        breaks: tracker.breaks.clone(),
+        trash: tracker.trash.clone(),
+    }
+}
+
+fn apply(app: &mut App, snapshot: Snapshot) {
+    let tracker = &mut app.tracker;
+    tracker.next_number = snapshot.next_number;
+    tracker.tasks = snapshot.tasks;
+    tracker.intervals = snapshot.intervals;
+    tracker.breaks = snapshot.breaks;
+    tracker.trash = snapshot.trash;
+    app.selected_index = match app.tracker.tasks.len() {
+        0 => None,
+        len => Some(app.selected_index.unwrap_or(0).min(len - 1)),
+    };
+}
+
+/// Remembers the state before handling an action, command or click, so
+/// whatever it changes becomes one undo step. Typing skips it.
+pub fn begin(app: &mut App) {
+    app.history.pending = Some(snapshot(app));
+}
+
+/// Turns the changes since `begin` into an undo step. Called once per tick
+/// before anything else runs, so timers advancing never count as a change.
+pub fn commit(app: &mut App) {
+    let Some(before) = app.history.pending.take() else {
+        return;
+    };
+    if before == snapshot(app) {
+        return;
+    }
+    let history = &mut app.history;
+    history.undo.push(before);
+    if history.undo.len() > DEPTH {
+        history.undo.remove(0);
+    }
+    history.redo.clear();
+    history.version += 1;
+}
+
+/// Steps back one change. Returns `false` when there is nothing to undo.
+pub fn undo(app: &mut App) -> bool {
+    app.history.pending = None;
+    let Some(previous) = app.history.undo.pop() else {
+        return false;
+    };
+    let current = snapshot(app);
+    app.history.redo.push(current);
+    app.history.version += 1;
+    apply(app, previous);
+    true
+}
+
+/// Reapplies the last undone change. Returns `false` when there is none.
+pub fn redo(app: &mut App) -> bool {
+    app.history.pending = None;
+    let Some(next) = app.history.redo.pop() else {
+        return false;
+    };
+    let current = snapshot(app);
+    app.history.undo.push(current);
+    app.history.version += 1;
+    apply(app, next);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::tracker::{TaskSpec, TaskStatus, Tracker};
+
+    use chrono::{Local, TimeZone};
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// An app with paused tasks of these names, each with half an hour
+    /// logged.
+    fn with_tasks(names: &[&str]) -> (App, MockClock) {
+        let clock = MockClock::new(Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap());
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(clock.clone());
+        for name in names {
+            let i = tracker
+                .add_task(TaskSpec::parse(name), TaskStatus::Active)
+                .unwrap();
+            clock.advance(Duration::from_secs(30 * 60));
+            tracker.pause(i, None);
+        }
+        (App::new(tracker), clock)
+    }
+
+    /// Makes `change` one undo step, the way the TUI runs an action.
+    fn step<T>(app: &mut App, change: impl FnOnce(&mut Tracker) -> T) -> T {
+        begin(app);
+        let out = change(&mut app.tracker);
+        commit(app);
+        out
+    }
+
+    fn names(app: &App) -> Vec<&str> {
+        app.tracker.tasks.iter().map(|t| t.name.as_str()).collect()
+    }
+
+    #[test]
+    fn undo_takes_back_an_added_task_and_redo_adds_it_again() {
+        let (mut app, _) = with_tasks(&["Alpha"]);
+        step(&mut app, |t| {
+            t.add_task(TaskSpec::parse("Bravo"), TaskStatus::Paused)
+                .unwrap()
+        });
+        assert_eq!(names(&app), ["Alpha", "Bravo"]);
+
+        assert!(undo(&mut app));
+        assert_eq!(names(&app), ["Alpha"]);
+        assert_eq!(app.tracker.next_number, 2);
+        assert!(redo(&mut app));
+        assert_eq!(names(&app), ["Alpha", "Bravo"]);
+        assert!(!redo(&mut app));
+    }
+
+    #[test]
+    fn undo_brings_a_deleted_task_back_with_its_time() {
+        let (mut app, _) = with_tasks(&["Alpha", "Bravo"]);
+        let intervals = app.tracker.intervals.clone();
+        let breaks = app.tracker.breaks.clone();
+        step(&mut app, |t| t.delete(0));
+        assert_eq!(names(&app), ["Bravo"]);
+        assert_eq!(app.tracker.trash.len(), 1);
+
+        assert!(undo(&mut app));
+        assert_eq!(names(&app), ["Alpha", "Bravo"]);
+        assert_eq!(app.tracker.intervals, intervals);
+        assert_eq!(app.tracker.breaks, breaks);
+        assert!(app.tracker.trash.is_empty());
+    }
+
+    #[test]
+    fn undo_of_a_pause_sets_the_task_running_again() {
+        let (mut app, clock) = with_tasks(&["Alpha"]);
+        step(&mut app, |t| t.resume(0));
+        clock.advance(Duration::from_secs(10 * 60));
+        step(&mut app, |t| t.pause(0, Some("lunch".to_string())));
+        assert_eq!(app.tracker.tasks[0].status, TaskStatus::Paused);
+        assert_eq!(app.tracker.intervals.len(), 2);
+
+        assert!(undo(&mut app));
+        let task = &app.tracker.tasks[0];
+        assert_eq!(task.status, TaskStatus::Active);
+        assert_eq!(app.tracker.intervals.len(), 1);
+        assert!(app.tracker.breaks[&task.id].started_at.is_none());
+    }
+
+    #[test]
+    fn undo_splits_a_merge_apart() {
+        let (mut app, _) = with_tasks(&["Alpha", "Bravo"]);
+        let before = app.tracker.intervals.clone();
+        step(&mut app, |t| t.merge(1, 0).unwrap());
+        assert_eq!(names(&app), ["Alpha"]);
+
+        assert!(undo(&mut app));
+        assert_eq!(names(&app), ["Alpha", "Bravo"]);
+        assert_eq!(app.tracker.intervals, before);
+    }
+
+    #[test]
+    fn trash_and_restore_undo_one_at_a_time() {
+        let (mut app, _) = with_tasks(&["Alpha", "Bravo"]);
+        step(&mut app, |t| t.delete(0));
+        step(&mut app, |t| t.restore(0).unwrap());
+        assert_eq!(names(&app), ["Bravo", "Alpha"]);
+
+        assert!(undo(&mut app));
+        assert_eq!(names(&app), ["Bravo"]);
+        assert_eq!(app.tracker.trash[0].task.name, "Alpha");
+        assert!(undo(&mut app));
+        assert_eq!(names(&app), ["Alpha", "Bravo"]);
+        assert!(app.tracker.trash.is_empty());
+        assert!(!undo(&mut app));
+    }
+
+    #[test]
+    fn a_new_change_clears_what_could_be_redone() {
+        let (mut app, _) = with_tasks(&["Alpha"]);
+        step(&mut app, |t| t.rename(0, "Alpha 2").unwrap());
+        assert!(undo(&mut app));
+        step(&mut app, |t| t.delete(0));
+
+        assert!(!redo(&mut app));
+        assert!(undo(&mut app));
+        assert_eq!(names(&app), ["Alpha"]);
+    }
+
+    #[test]
+    fn input_that_changes_nothing_makes_no_step() {
+        let (mut app, _) = with_tasks(&["Alpha"]);
+        let version = app.history.version();
+        step(&mut app, |t| t.find("Alpha"));
+        assert_eq!(app.history.version(), version);
+        assert!(!undo(&mut app));
+    }
+}
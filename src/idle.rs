@@ -0,0 +1,323 @@
+use crate::pomodoro;
+use crate::tracker::{Interval, TaskId, TaskStatus};
+use crate::util::App;
+
+use chrono::{DateTime, Local, TimeDelta};
+
+/// Why the TUI thinks nobody was working.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AwayReason {
+    /// No key press for `idle.idle_minutes`.
+    NoInput,
+    /// Two ticks further apart than `idle.suspend_minutes`, e.g. a laptop
+    /// that went to sleep.
+    Suspended,
+}
+
+/// A stretch nobody was at the keyboard while tasks kept running, waiting
+/// for the user to say what it was.
+#[derive(Debug, Clone)]
+pub struct Away {
+    pub since: DateTime<Local>,
+    pub reason: AwayReason,
+    /// Ids of the tasks that were running through the gap.
+    pub tasks: Vec<TaskId>,
+    /// The task the time would be reassigned to.
+    pub target: TaskId,
+}
+
+/// What to do with the time spent away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decision {
+    Keep,
+    Discard,
+    Break,
+    /// Move the time to `Away::target`.
+    Reassign,
+}
+
+/// Notes a key press.
+pub fn touch(app: &mut App) {
+    app.last_input = app.tracker.now();
+}
+
+/// Starts watching from now, as if a key was just pressed on a fresh tick.
+/// For handing over from a screen that does not run the idle check.
+pub fn restart(app: &mut App) {
+    touch(app);
+    app.last_tick = app.last_input;
+}
+
+/// Looks for a gap since the last tick or key press. Called once per tick,
+/// before drawing; does nothing while a gap is already waiting for a
+/// decision or nothing is running. Simulated minutes pass faster than
+/// anyone can type, so a `--simulate` session is never idle.
+pub fn tick(app: &mut App) {
+    let now = app.tracker.now();
+    let last_tick = std::mem::replace(&mut app.last_tick, now);
+    let config = &app.config.idle;
+    if !config.enabled || app.simulate.is_some() || app.away.is_some() {
+        return;
+    }
+
+    let running: Vec<TaskId> = app
+        .tracker
+        .tasks
+        .iter()
+        .filter(|t| matches!(t.status, TaskStatus::Active))
+        .map(|t| t.id)
+        .collect();
+    if running.is_empty() {
+        return;
+    }
+
+    let minutes = |m: u64| TimeDelta::minutes(m as i64);
+    let (since, reason) = if now - last_tick >= minutes(config.suspend_minutes) {
+        (last_tick, AwayReason::Suspended)
+    } else if now - app.last_input >= minutes(config.idle_minutes) {
+        (app.last_input, AwayReason::NoInput)
+    } else {
+        return;
+    };
+
+    // Something is running, so there is a first task to fall back on
+    let target = app
+        .selected_index
+        .and_then(|i| app.tracker.tasks.get(i))
+        .unwrap_or(&app.tracker.tasks[0])
+        .id;
+    app.away = Some(Away {
+        since,
+        reason,
+        tasks: running,
+        target,
+    });
+}
+
+/// Moves the reassign target `step` tasks on, wrapping around the list.
+pub fn cycle_target(app: &mut App, step: isize) {
+    let Some(away) = &mut app.away else {
+        return;
+    };
+    let tasks = &app.tracker.tasks;
+    if tasks.is_empty() {
+        return;
+    }
+    let at = app.tracker.position(away.target).unwrap_or(0) as isize;
+    let next = (at + step).rem_euclid(tasks.len() as isize) as usize;
+    away.target = tasks[next].id;
+}
+
+/// Settles the pending gap. Every task that ran through it gets the stretch
+/// before the gap logged with a note saying what happened next, then starts
+/// counting again from now.
+pub fn resolve(app: &mut App, decision: Decision) {
+    let Some(away) = app.away.take() else {
+        return;
+    };
+    let now = app.tracker.now();
+    let target = app
+        .tracker
+        .position(away.target)
+        .map(|i| (away.target, app.tracker.tasks[i].name.clone()));
+
+    let what = match (decision, &target) {
+        (Decision::Keep, _) => "kept".to_string(),
+        (Decision::Discard, _) => "discarded".to_string(),
+        (Decision::Break, _) => "taken as a break".to_string(),
+        (Decision::Reassign, Some((_, name))) => format!("moved to '{name}'"),
+        (Decision::Reassign, None) => "discarded".to_string(),
+    };
+    let note = format!(
+        "away {}–{} ({}): {what}",
+        away.since.format("%H:%M"),
+        now.format("%H:%M"),
+        match away.reason {
+            AwayReason::NoInput => "no input",
+            AwayReason::Suspended => "suspended",
+        }
+    );
+
+    let mut moved_from = Vec::new();
+    for &id in &away.tasks {
+        let Some(task) = app.tracker.tasks.iter_mut().find(|t| t.id == id) else {
+            continue;
+        };
+        let Some(start) = task
+            .started_at
+            .filter(|_| matches!(task.status, TaskStatus::Active))
+        else {
+            continue;
+        };
+        task.started_at = Some(now);
+        moved_from.push(task.name.clone());
+
+        let cut = away.since.max(start);
+        if cut > start {
+            app.tracker.intervals.push(Interval {
+                task_id: id,
+                start,
+                end: cut,
+                note: Some(note.clone()),
+            });
+        }
+        match decision {
+            Decision::Keep => app.tracker.intervals.push(Interval {
+                task_id: id,
+                start: cut,
+                end: now,
+                note: Some(note.clone()),
+            }),
+            Decision::Break => pomodoro::record_break(&mut app.tracker, id, cut, now),
+            Decision::Discard | Decision::Reassign => {}
+        }
+    }
+
+    if decision == Decision::Reassign
+        && let Some((id, _)) = target
+        && !moved_from.is_empty()
+    {
+        app.tracker.intervals.push(Interval {
+            task_id: id,
+            start: away.since,
+            end: now,
+            note: Some(format!("{note} from '{}'", moved_from.join("', '"))),
+        });
+    }
+
+    app.tracker.intervals.sort_by_key(|iv| iv.start);
+    app.last_input = now;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::tracker::{TaskSpec, Tracker};
+
+    use chrono::TimeZone;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// "Write" running since 9:00, "Email" paused, and a gap since 9:20
+    /// that is settled at 10:00.
+    fn away_since_nine_twenty() -> App {
+        let nine = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let clock = MockClock::new(nine);
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(clock.clone());
+        tracker.start(TaskSpec::parse("Write")).unwrap();
+        tracker
+            .add_task(TaskSpec::parse("Email"), TaskStatus::Paused)
+            .unwrap();
+        clock.advance(Duration::from_secs(60 * 60));
+
+        let mut app = App::new(tracker);
+        app.away = Some(Away {
+            since: nine + TimeDelta::minutes(20),
+            reason: AwayReason::NoInput,
+            tasks: vec![app.tracker.tasks[0].id],
+            target: app.tracker.tasks[1].id,
+        });
+        app
+    }
+
+    fn logged(app: &App) -> Vec<(String, String, String)> {
+        app.tracker
+            .intervals
+            .iter()
+            .map(|iv| {
+                let task = app.tracker.tasks.iter().find(|t| t.id == iv.task_id);
+                (
+                    task.unwrap().name.clone(),
+                    iv.start.format("%H:%M").to_string(),
+                    iv.end.format("%H:%M").to_string(),
+                )
+            })
+            .collect()
+    }
+
+    fn row(name: &str, start: &str, end: &str) -> (String, String, String) {
+        (name.to_string(), start.to_string(), end.to_string())
+    }
+
+    #[test]
+    fn keep_logs_the_gap_and_the_task_runs_on() {
+        let mut app = away_since_nine_twenty();
+        resolve(&mut app, Decision::Keep);
+        assert_eq!(
+            logged(&app),
+            vec![
+                row("Write", "09:00", "09:20"),
+                row("Write", "09:20", "10:00")
+            ]
+        );
+        let note = app.tracker.intervals[0].note.as_deref();
+        assert_eq!(note, Some("away 09:20–10:00 (no input): kept"));
+        let task = &app.tracker.tasks[0];
+        assert_eq!(task.status, TaskStatus::Active);
+        assert_eq!(task.started_at, Some(app.tracker.now()));
+        assert!(app.away.is_none());
+    }
+
+    #[test]
+    fn discard_drops_the_gap() {
+        let mut app = away_since_nine_twenty();
+        resolve(&mut app, Decision::Discard);
+        assert_eq!(logged(&app), vec![row("Write", "09:00", "09:20")]);
+    }
+
+    #[test]
+    fn break_records_the_gap_as_a_break() {
+        let mut app = away_since_nine_twenty();
+        let id = app.tracker.tasks[0].id;
+        resolve(&mut app, Decision::Break);
+        assert_eq!(logged(&app), vec![row("Write", "09:00", "09:20")]);
+        let history = &app.tracker.breaks[&id].history;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].actual, Duration::from_secs(40 * 60));
+    }
+
+    #[test]
+    fn reassign_moves_the_gap_to_the_target() {
+        let mut app = away_since_nine_twenty();
+        resolve(&mut app, Decision::Reassign);
+        assert_eq!(
+            logged(&app),
+            vec![
+                row("Write", "09:00", "09:20"),
+                row("Email", "09:20", "10:00")
+            ]
+        );
+        let note = app.tracker.intervals[1].note.as_deref().unwrap();
+        assert!(note.ends_with("moved to 'Email' from 'Write'"), "{note}");
+    }
+
+    #[test]
+    fn the_target_stays_with_its_task_when_the_rows_move() {
+        let mut app = away_since_nine_twenty();
+        app.tracker.tasks.reverse();
+        resolve(&mut app, Decision::Reassign);
+        let note = app.tracker.intervals[1].note.as_deref().unwrap();
+        assert!(note.ends_with("moved to 'Email' from 'Write'"), "{note}");
+    }
+
+    #[test]
+    fn the_target_cycles_around_the_tasks() {
+        let mut app = away_since_nine_twenty();
+        let ids: Vec<TaskId> = app.tracker.tasks.iter().map(|t| t.id).collect();
+        cycle_target(&mut app, 1);
+        assert_eq!(app.away.as_ref().unwrap().target, ids[0]);
+        cycle_target(&mut app, -1);
+        cycle_target(&mut app, -1);
+        assert_eq!(app.away.as_ref().unwrap().target, ids[0]);
+    }
+
+    #[test]
+    fn nothing_happens_without_a_gap() {
+        let mut app = away_since_nine_twenty();
+        app.away = None;
+        resolve(&mut app, Decision::Keep);
+        assert!(app.tracker.intervals.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::idle::{self, AwayReason, Decision};
+use crate::keymap::Action;
+use crate::redundancy_warning::centered_rect;
+use crate::util::{App, format_hms};
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+
+/// The actions the prompt handles, whatever the mode behind it.
+pub const ACTIONS: &[Action] = &[
+    Action::KeepAway,
+    Action::DiscardAway,
+    Action::BreakAway,
+    Action::ReassignAway,
+    Action::PrevTarget,
+    Action::NextTarget,
+];
+
+/// Draws the "you were away" prompt over whatever view is showing.
+pub fn pop_up(frame: &mut Frame, app: &App) {
+    let Some(away) = &app.away else {
+        return;
+    };
+    let colors = app.config.colors;
+
+    let area = centered_rect(60, 30, frame.area());
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .title("YOU WERE AWAY")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::default().bg(colors.background).fg(colors.highlight));
+
+    let gap = (app.tracker.now() - away.since)
+        .to_std()
+        .unwrap_or_default();
+    let cause = match away.reason {
+        AwayReason::NoInput => format!("no input since {}", away.since.format("%H:%M")),
+        AwayReason::Suspended => format!("the clock jumped at {}", away.since.format("%H:%M")),
+    };
+    let running: Vec<&str> = app
+        .tracker
+        .tasks
+        .iter()
+        .filter(|t| away.tasks.contains(&t.id))
+        .map(|t| t.name.as_str())
+        .collect();
+    let target = app
+        .tracker
+        .position(away.target)
+        .map_or("-", |i| app.tracker.tasks[i].name.as_str());
+    let keys = &app.keymap;
+    let decisions: Vec<String> = [Action::KeepAway, Action::DiscardAway, Action::BreakAway]
+        .into_iter()
+        .filter_map(|a| keys.hint(a))
+        .collect();
+    let reassign = keys.key(Action::ReassignAway).map_or(String::new(), |key| {
+        format!("{key} {} to: < {target} >", Action::ReassignAway.label())
+    });
+    let choose = match (keys.key(Action::PrevTarget), keys.key(Action::NextTarget)) {
+        (Some(prev), Some(next)) => format!("   {prev}/{next} Choose"),
+        _ => String::new(),
+    };
+
+    let lines = vec![
+        Line::from(format!("Away for {} ({cause})", format_hms(gap))),
+        Line::from(format!("while tracking: {}", running.join(", "))),
+        Line::from(""),
+        Line::from(decisions.join("   ")).style(Style::new().fg(colors.heading)),
+        Line::from(format!("{reassign}{choose}")).style(Style::new().fg(colors.heading)),
+    ];
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(Paragraph::new(lines).alignment(Alignment::Center), inner);
+}
+
+/// Handles a key while the prompt is open. Returns `false` when there is no
+/// prompt, so the view handles the key itself.
+pub fn handle_key(app: &mut App, key: KeyEvent) -> bool {
+    if app.away.is_none() {
+        return false;
+    }
+
+    match app.keymap.action_among(&key, ACTIONS) {
+        Some(Action::KeepAway) => idle::resolve(app, Decision::Keep),
+        Some(Action::DiscardAway) => idle::resolve(app, Decision::Discard),
+        Some(Action::BreakAway) => idle::resolve(app, Decision::Break),
+        Some(Action::ReassignAway) => idle::resolve(app, Decision::Reassign),
+        Some(Action::PrevTarget) => idle::cycle_target(app, -1),
+        Some(Action::NextTarget) => idle::cycle_target(app, 1),
+        _ => {}
+    }
+    true
+}
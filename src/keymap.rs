@@ -0,0 +1,626 @@
+//! Key bindings. The views ask the active keymap which `Action` a key press
+//! stands for, and the MENU footer and the `?` help are generated from it,
+//! so what they show is always what works.
+//!
+//! The `[keys]` table of the config file picks a preset and can rebind
+//! single actions:
+//!
+//! ```toml
+//! [keys]
+//! preset = "vi"
+//! bindings = { pause = ["p", "ctrl+p"], switch_view = ["tab"] }
+//! ```
+
+use crate::config::KeysConfig;
+use crate::idle_ui;
+use crate::tui::Mode;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// Everything a key can do in the TUI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    SwitchView,
+    Help,
+    Command,
+    /// Opens the `/` filter over the TASKS tables.
+    Search,
+    Undo,
+    Redo,
+    AddTask,
+    Pause,
+    Resume,
+    Delete,
+    Rename,
+    NextTask,
+    PrevTask,
+    /// Pages through the TASKS table, or the rows of the Settings view.
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    /// Pages through the Productivity Logs of the Timer view.
+    LogsPageUp,
+    LogsPageDown,
+    LogsTop,
+    LogsBottom,
+    /// Turns the TASKS sort descending, or moves it on to the next column.
+    Sort,
+    /// Moves the focus between the textarea and the TASKS table.
+    Focus,
+    GroupBy,
+    Filter,
+    Collapse,
+    GraphLeaves,
+    DayReport,
+    WeekReport,
+    MonthReport,
+    Breakdown,
+    EarlierRange,
+    LaterRange,
+    ExportCsv,
+    ExportJson,
+    /// Starts typing a new value for the selected setting.
+    EditSetting,
+    /// Logs the time spent away as if nobody left, from the away prompt.
+    KeepAway,
+    DiscardAway,
+    BreakAway,
+    /// Moves the time spent away to the task picked in the prompt.
+    ReassignAway,
+    PrevTarget,
+    NextTarget,
+}
+
+impl Action {
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Quit => "Exit",
+            Action::SwitchView => "Switch View",
+            Action::Help => "Help",
+            Action::Command => "Command",
+            Action::Search => "Search",
+            Action::Undo => "Undo",
+            Action::Redo => "Redo",
+            Action::AddTask => "Add Task",
+            Action::Pause => "Pause Task",
+            Action::Resume => "Resume Task",
+            Action::Delete => "Delete Task",
+            Action::Rename => "Rename",
+            Action::NextTask => "Next Task",
+            Action::PrevTask => "Previous Task",
+            Action::PageUp => "Page Up",
+            Action::PageDown => "Page Down",
+            Action::Top => "Top",
+            Action::Bottom => "Bottom",
+            Action::LogsPageUp => "Logs Page Up",
+            Action::LogsPageDown => "Logs Page Down",
+            Action::LogsTop => "Logs Top",
+            Action::LogsBottom => "Logs Bottom",
+            Action::Sort => "Sort",
+            Action::Focus => "Focus",
+            Action::GroupBy => "Group",
+            Action::Filter => "Filter",
+            Action::Collapse => "Collapse",
+            Action::GraphLeaves => "Graph Leaves/Rolled up",
+            Action::DayReport => "Per Day",
+            Action::WeekReport => "Per Week",
+            Action::MonthReport => "Per Month",
+            Action::Breakdown => "Breakdown",
+            Action::EarlierRange => "Earlier",
+            Action::LaterRange => "Later",
+            Action::ExportCsv => "Export CSV",
+            Action::ExportJson => "Export JSON",
+            Action::EditSetting => "Edit Setting",
+            Action::KeepAway => "Keep it",
+            Action::DiscardAway => "Discard it",
+            Action::BreakAway => "Count it as a break",
+            Action::ReassignAway => "Reassign it",
+            Action::PrevTarget => "Previous Target",
+            Action::NextTarget => "Next Target",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Default,
+    Vi,
+    Emacs,
+}
+
+impl Preset {
+    fn bindings(self) -> &'static [(Action, &'static [&'static str])] {
+        use Action::*;
+        match self {
+            Preset::Default => &[
+                (Quit, &["esc"]),
+                (SwitchView, &["T", "ctrl+t"]),
+                (Help, &["?", "f1"]),
+                (Command, &[":"]),
+                (Search, &["/"]),
+                (Undo, &["ctrl+z"]),
+                (Redo, &["ctrl+y"]),
+                (AddTask, &["enter"]),
+                (Pause, &["P", "ctrl+p"]),
+                (Resume, &["R", "ctrl+r"]),
+                (Delete, &["delete"]),
+                (Rename, &["f2"]),
+                (NextTask, &["down"]),
+                (PrevTask, &["up"]),
+                (PageUp, &["pageup"]),
+                (PageDown, &["pagedown"]),
+                (Top, &["home"]),
+                (Bottom, &["end"]),
+                (LogsPageUp, &["shift+pageup"]),
+                (LogsPageDown, &["shift+pagedown"]),
+                (LogsTop, &["shift+home"]),
+                (LogsBottom, &["shift+end"]),
+                (Sort, &["S"]),
+                (Focus, &["tab"]),
+                (GroupBy, &["G"]),
+                (Filter, &["F"]),
+                (Collapse, &["C"]),
+                (GraphLeaves, &["L"]),
+                (DayReport, &["d"]),
+                (WeekReport, &["w"]),
+                (MonthReport, &["m"]),
+                (Breakdown, &["b"]),
+                (EarlierRange, &["left"]),
+                (LaterRange, &["right"]),
+                (ExportCsv, &["x"]),
+                (ExportJson, &["j"]),
+                (EditSetting, &["enter"]),
+                (KeepAway, &["K", "k", "esc"]),
+                (DiscardAway, &["D", "d"]),
+                (BreakAway, &["B", "b"]),
+                (ReassignAway, &["R", "r"]),
+                (PrevTarget, &["left"]),
+                (NextTarget, &["right"]),
+            ],
+            Preset::Vi => &[
+                (Quit, &["q", "esc"]),
+                (SwitchView, &["t"]),
+                (Help, &["?"]),
+                (Command, &[":"]),
+                (Search, &["/"]),
+                (Undo, &["u"]),
+                (Redo, &["ctrl+r"]),
+                (AddTask, &["enter"]),
+                (Pause, &["p"]),
+                (Resume, &["r"]),
+                (Delete, &["x", "delete"]),
+                (Rename, &["c"]),
+                (NextTask, &["j", "down"]),
+                (PrevTask, &["k", "up"]),
+                (PageUp, &["ctrl+b", "pageup"]),
+                (PageDown, &["ctrl+f", "pagedown"]),
+                (Top, &["home"]),
+                (Bottom, &["G", "end"]),
+                (LogsPageUp, &["ctrl+u", "shift+pageup"]),
+                (LogsPageDown, &["ctrl+d", "shift+pagedown"]),
+                (LogsTop, &["shift+home"]),
+                (LogsBottom, &["shift+end"]),
+                (Sort, &["s"]),
+                (Focus, &["i", "tab"]),
+                (GroupBy, &["g"]),
+                (Filter, &["f"]),
+                (Collapse, &["z"]),
+                (GraphLeaves, &["L"]),
+                (DayReport, &["d"]),
+                (WeekReport, &["w"]),
+                (MonthReport, &["m"]),
+                (Breakdown, &["b"]),
+                (EarlierRange, &["h", "left"]),
+                (LaterRange, &["l", "right"]),
+                (ExportCsv, &["x"]),
+                (ExportJson, &["J"]),
+                (EditSetting, &["enter", "e"]),
+                (KeepAway, &["k", "esc"]),
+                (DiscardAway, &["d"]),
+                (BreakAway, &["b"]),
+                (ReassignAway, &["r"]),
+                (PrevTarget, &["h", "left"]),
+                (NextTarget, &["l", "right"]),
+            ],
+            Preset::Emacs => &[
+                (Quit, &["esc", "ctrl+q"]),
+                (SwitchView, &["alt+t"]),
+                (Help, &["f1"]),
+                (Command, &["alt+x"]),
+                (Search, &["ctrl+s"]),
+                (Undo, &["ctrl+z"]),
+                (Redo, &["alt+z"]),
+                (AddTask, &["enter"]),
+                (Pause, &["alt+p"]),
+                (Resume, &["alt+r"]),
+                (Delete, &["delete"]),
+                (Rename, &["f2"]),
+                (NextTask, &["ctrl+n", "down"]),
+                (PrevTask, &["ctrl+p", "up"]),
+                (PageUp, &["alt+v", "pageup"]),
+                (PageDown, &["ctrl+v", "pagedown"]),
+                (Top, &["alt+<", "home"]),
+                (Bottom, &["alt+>", "end"]),
+                (LogsPageUp, &["shift+pageup"]),
+                (LogsPageDown, &["shift+pagedown"]),
+                (LogsTop, &["shift+home"]),
+                (LogsBottom, &["shift+end"]),
+                (Sort, &["alt+s"]),
+                (Focus, &["tab"]),
+                (GroupBy, &["alt+g"]),
+                (Filter, &["alt+f"]),
+                (Collapse, &["alt+c"]),
+                (GraphLeaves, &["alt+l"]),
+                (DayReport, &["alt+d"]),
+                (WeekReport, &["alt+w"]),
+                (MonthReport, &["alt+m"]),
+                (Breakdown, &["alt+b"]),
+                (EarlierRange, &["ctrl+b", "left"]),
+                (LaterRange, &["ctrl+f", "right"]),
+                (ExportCsv, &["alt+c"]),
+                (ExportJson, &["alt+j"]),
+                (EditSetting, &["enter"]),
+                (KeepAway, &["k", "esc", "ctrl+g"]),
+                (DiscardAway, &["d"]),
+                (BreakAway, &["b"]),
+                (ReassignAway, &["r"]),
+                (PrevTarget, &["ctrl+b", "left"]),
+                (NextTarget, &["ctrl+f", "right"]),
+            ],
+        }
+    }
+}
+
+/// One key with its modifiers, written like `ctrl+z`, `T`, `f2` or `esc`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub fn parse(input: &str) -> Result<Key, String> {
+        // A trailing `+` is the plus key itself, as in `ctrl++`
+        let (mods, name) = match input.strip_suffix("++") {
+            Some(mods) => (mods, "+"),
+            None if input == "+" => ("", "+"),
+            None => input.rsplit_once('+').unwrap_or(("", input)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match m.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{m}' in '{input}'")),
+            };
+        }
+
+        let mut chars = name.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match name.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "space" => KeyCode::Char(' '),
+                f => match f.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{name}' in '{input}'")),
+                },
+            },
+        };
+        Ok(Key { code, modifiers })
+    }
+
+    /// Shift is part of the character itself (`T`, `?`), so it only counts
+    /// for keys that are not characters.
+    fn matches(&self, event: &KeyEvent) -> bool {
+        let mut modifiers = event.modifiers;
+        if matches!(event.code, KeyCode::Char(_)) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == event.code && self.modifiers == modifiers
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{c}"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Keys that edit the text being typed rather than trigger an action.
+pub fn is_text_key(event: &KeyEvent) -> bool {
+    let modified = event
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+    match event.code {
+        KeyCode::Char(_) => !modified,
+        KeyCode::Backspace
+        | KeyCode::Left
+        | KeyCode::Right
+        | KeyCode::Up
+        | KeyCode::Down
+        | KeyCode::Home
+        | KeyCode::End => true,
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    pub preset: Preset,
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(&KeysConfig::default()).expect("the default keymap is valid")
+    }
+}
+
+impl Keymap {
+    /// The preset with the configured bindings laid over it. Fails on keys
+    /// that do not parse and on one key meaning two actions in a mode.
+    pub fn new(config: &KeysConfig) -> Result<Keymap, String> {
+        let mut bindings = Vec::new();
+        for &(action, keys) in config.preset.bindings() {
+            let keys = match config.bindings.get(&action) {
+                Some(custom) => custom
+                    .iter()
+                    .map(|k| Key::parse(k))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("keys.bindings.{}: {err}", name(action)))?,
+                None => keys
+                    .iter()
+                    .map(|k| Key::parse(k))
+                    .collect::<Result<_, _>>()?,
+            };
+            bindings.push((action, keys));
+        }
+        let keymap = Keymap {
+            preset: config.preset,
+            bindings,
+        };
+
+        let scopes = Mode::ALL.map(|mode| {
+            (
+                format!("{} mode", mode.title().to_lowercase()),
+                mode.actions(),
+            )
+        });
+        let away = ("away prompt".to_string(), idle_ui::ACTIONS);
+        for (scope, actions) in scopes.into_iter().chain([away]) {
+            for (n, &a) in actions.iter().enumerate() {
+                for &b in &actions[n + 1..] {
+                    if let Some(key) = keymap.keys(a).iter().find(|k| keymap.keys(b).contains(k)) {
+                        return Err(format!(
+                            "keys: '{key}' is bound to both {} and {} in the {scope}",
+                            name(a),
+                            name(b),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(keymap)
+    }
+
+    /// The action a key press stands for in `mode`. While `typing`, plain
+    /// characters and cursor keys belong to the textarea and never trigger
+    /// an action, so `T`, `P` or `R` can be part of a task name.
+    pub fn action(&self, event: &KeyEvent, mode: Mode, typing: bool) -> Option<Action> {
+        if typing && is_text_key(event) {
+            return None;
+        }
+        self.action_among(event, mode.actions())
+    }
+
+    /// The action a key press stands for among `actions`, for prompts that
+    /// take over the keyboard whatever the mode.
+    pub fn action_among(&self, event: &KeyEvent, actions: &[Action]) -> Option<Action> {
+        actions
+            .iter()
+            .copied()
+            .find(|&a| self.keys(a).iter().any(|k| k.matches(event)))
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, keys)| keys.as_slice())
+    }
+
+    /// `<Ctrl+Z>`, the first key bound to the action.
+    pub fn key(&self, action: Action) -> Option<String> {
+        self.keys(action).first().map(|key| format!("<{key}>"))
+    }
+
+    /// `<Ctrl+Z> Undo`, for footers and titles.
+    pub fn hint(&self, action: Action) -> Option<String> {
+        Some(format!("{} {}", self.key(action)?, action.label()))
+    }
+
+    /// Footer entries of `mode` with their actions, skipping unbound ones.
+    pub fn footer(&self, mode: Mode) -> Vec<(Action, String)> {
+        mode.footer()
+            .iter()
+            .filter_map(|&a| Some((a, self.hint(a)?)))
+            .collect()
+    }
+
+    /// Every key of every one of `actions`, for the help screen.
+    pub fn help(&self, actions: &[Action]) -> Vec<(String, &'static str)> {
+        actions
+            .iter()
+            .filter(|&&a| !self.keys(a).is_empty())
+            .map(|&a| {
+                let keys: Vec<String> = self.keys(a).iter().map(|k| format!("<{k}>")).collect();
+                (keys.join(" "), a.label())
+            })
+            .collect()
+    }
+}
+
+/// The action's name in the config file.
+fn name(action: Action) -> String {
+    toml::Value::try_from(action)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_else(|| format!("{action:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+        Key { code, modifiers }
+    }
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn key_parse_reads_modifiers_and_names() {
+        let parse = |input| Key::parse(input).unwrap();
+        assert_eq!(parse("T"), key(KeyCode::Char('T'), KeyModifiers::NONE));
+        assert_eq!(
+            parse("ctrl+z"),
+            key(KeyCode::Char('z'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            parse("Ctrl+Alt+Del"),
+            key(KeyCode::Delete, KeyModifiers::CONTROL | KeyModifiers::ALT)
+        );
+        assert_eq!(parse("shift+f5"), key(KeyCode::F(5), KeyModifiers::SHIFT));
+        assert_eq!(parse("space"), key(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert_eq!(parse("+"), key(KeyCode::Char('+'), KeyModifiers::NONE));
+        assert_eq!(
+            parse("ctrl++"),
+            key(KeyCode::Char('+'), KeyModifiers::CONTROL)
+        );
+
+        assert_eq!(
+            Key::parse("hyper+x").unwrap_err(),
+            "unknown modifier 'hyper' in 'hyper+x'"
+        );
+        assert_eq!(Key::parse("f13").unwrap_err(), "unknown key 'f13' in 'f13'");
+        assert!(Key::parse("ctrl+").is_err());
+    }
+
+    #[test]
+    fn keys_print_the_way_hints_show_them() {
+        let shown = |input| Key::parse(input).unwrap().to_string();
+        assert_eq!(shown("ctrl+z"), "Ctrl+Z");
+        assert_eq!(shown("T"), "T");
+        assert_eq!(shown("space"), "Space");
+        assert_eq!(shown("pagedown"), "PageDown");
+    }
+
+    #[test]
+    fn shift_only_counts_for_keys_that_are_not_characters() {
+        let upper_t = Key::parse("T").unwrap();
+        assert!(upper_t.matches(&press(KeyCode::Char('T'), KeyModifiers::SHIFT)));
+        let tab = Key::parse("tab").unwrap();
+        assert!(!tab.matches(&press(KeyCode::Tab, KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn every_preset_is_free_of_conflicts() {
+        for preset in [Preset::Default, Preset::Vi, Preset::Emacs] {
+            let config = KeysConfig {
+                preset,
+                ..KeysConfig::default()
+            };
+            assert!(Keymap::new(&config).is_ok(), "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn the_away_prompt_has_keys_of_its_own() {
+        let mut config = KeysConfig::default();
+        config
+            .bindings
+            .insert(Action::DiscardAway, vec!["k".to_string()]);
+        assert_eq!(
+            Keymap::new(&config).unwrap_err(),
+            "keys: 'k' is bound to both keep_away and discard_away in the away prompt"
+        );
+
+        // The modes' keys do not get in its way
+        let keymap = Keymap::default();
+        let d = press(KeyCode::Char('D'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(&d, Mode::Reports, false), None);
+        assert_eq!(
+            keymap.action_among(&d, idle_ui::ACTIONS),
+            Some(Action::DiscardAway)
+        );
+    }
+
+    #[test]
+    fn keymap_new_rejects_conflicting_and_unreadable_bindings() {
+        let mut config = KeysConfig::default();
+        config
+            .bindings
+            .insert(Action::Pause, vec!["esc".to_string()]);
+        assert_eq!(
+            Keymap::new(&config).unwrap_err(),
+            "keys: 'Esc' is bound to both quit and pause in the tasks mode"
+        );
+
+        config
+            .bindings
+            .insert(Action::Pause, vec!["ctrl+nope".to_string()]);
+        assert_eq!(
+            Keymap::new(&config).unwrap_err(),
+            "keys.bindings.pause: unknown key 'nope' in 'ctrl+nope'"
+        );
+
+        config.bindings.insert(Action::Pause, vec!["p".to_string()]);
+        let keymap = Keymap::new(&config).unwrap();
+        let p = press(KeyCode::Char('p'), KeyModifiers::NONE);
+        assert_eq!(keymap.action(&p, Mode::Tasks, false), Some(Action::Pause));
+        assert_eq!(keymap.action(&p, Mode::Tasks, true), None);
+        assert_eq!(keymap.key(Action::Pause), Some("<p>".to_string()));
+    }
+}
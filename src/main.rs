@@ -1,3 +1,4 @@
+use clap::Parser;
 use color_eyre::Result;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -7,27 +8,94 @@ use crossterm::{
 use ratatui::{Terminal, backend::CrosstermBackend};
 
 use std::io::stdout;
+use std::sync::Arc;
+use std::time::Duration;
 
 mod banner;
+mod cli;
+mod clock;
+mod command;
+mod config;
+mod export;
+mod help_ui;
+mod history;
+mod idle;
+mod idle_ui;
+mod keymap;
+mod mouse;
 mod task_ui;
 use banner::tui_banner;
+mod pomodoro;
 mod redundancy_warning;
+mod report;
+mod report_ui;
+mod search;
+mod settings_ui;
+mod storage;
 mod timer;
+mod tracker;
+mod tui;
+#[cfg(test)]
+mod ui_tests;
 mod util;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = cli::Cli::parse();
+
+    let config_path = args.config.clone().unwrap_or_else(config::config_path);
+    if args.config.is_some() && !config_path.exists() {
+        eprintln!("chronos: no config file at {}", config_path.display());
+        std::process::exit(1);
+    }
+    let config = match config::load(&config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("chronos: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    // Subcommands run without the TUI and exit right away
+    if let Some(command) = args.command {
+        if let Err(err) = cli::run_command(command, args.json, config) {
+            eprintln!("chronos: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let mut store = storage::Autosave::new(storage::data_path());
+    let mut tracker = store.load()?;
+    tracker.configure(&config);
+    if let Some(speed) = args.simulate {
+        tracker.clock = Arc::new(clock::SimulatedClock::new(speed));
+    }
+    let mut app = util::App::new(tracker);
+    app.tick_rate = Duration::from_millis(args.tick_rate);
+    app.simulate = args.simulate;
+    app.keymap = keymap::Keymap::new(&config.keys)?;
+    app.config = config;
+    app.config_path = Some(config_path);
+    if args.simulate.is_none() {
+        app.autosave = Some(store);
+    }
+
     // Terminal Setup
     enable_raw_mode()?;
     let mut stdout = stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?; // enter TUI mode
     let backend = CrosstermBackend::new(stdout);
-    let terminal = Terminal::new(backend)?;
+    let mut terminal = Terminal::new(backend)?;
 
     // Run app
-    let result = tui_banner(terminal);
+    let result = tui_banner(&mut terminal, app, &mut tui::TerminalEvents);
 
     // Restore terminal
     disable_raw_mode()?;
     execute!(std::io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
 
-    result
+    // The session saved itself as it went
+    if let Some(app) = result? && app.simulate.is_some() {
+        eprintln!("chronos: simulated session, nothing was saved");
+    }
+    Ok(())
 }
@@ -0,0 +1,133 @@
+//! Mouse support. The chrome and the views register what sits where as they
+//! draw each frame, and the loop looks clicks and wheel turns up here, so a
+//! hit always matches what is on screen.
+
+use crate::keymap::Action;
+use crate::search;
+use crate::tui::{self, Mode, Step};
+use crate::util::{App, SortBy};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Flex, Layout, Position, Rect};
+
+use std::rc::Rc;
+
+/// Something on screen that reacts to the mouse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    /// A tab in the MODES row.
+    Mode(Mode),
+    /// An entry in the MENU footer.
+    Action(Action),
+    /// A row of a TASKS table, by task index.
+    Task(usize),
+    /// The rest of a TASKS table; the wheel moves the selection.
+    TaskList,
+    /// A TASKS column header; a click sorts by it.
+    Sort(SortBy),
+    /// A task suggested under the textarea; a click resumes it.
+    Suggestion(usize),
+    /// The Productivity Logs; the wheel scrolls them.
+    Logs,
+    /// A row of the Settings table, by its position.
+    Setting(usize),
+}
+
+/// The targets of the last frame drawn.
+#[derive(Debug, Default)]
+pub struct Hits(Vec<(Rect, Target)>);
+
+impl Hits {
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn add(&mut self, area: Rect, target: Target) {
+        self.0.push((area, target));
+    }
+
+    /// Registers a TASKS table drawn inside `area` below a one-line header,
+    /// where `rows` maps each row to its task and `offset` is the first row
+    /// shown.
+    pub fn add_rows(&mut self, area: Rect, offset: usize, rows: &[Option<usize>]) {
+        self.add(area, Target::TaskList);
+        for (y, row) in (area.y + 1..area.bottom()).zip(rows.iter().skip(offset)) {
+            if let Some(i) = row {
+                self.add(Rect::new(area.x, y, area.width, 1), Target::Task(*i));
+            }
+        }
+    }
+
+    /// Registers the sortable column headers of a table drawn inside `area`.
+    pub fn add_headers(&mut self, area: Rect, widths: &[Constraint], columns: &[Option<SortBy>]) {
+        let header = Rect { height: 1, ..area };
+        for (cell, column) in cells(header, widths).iter().zip(columns) {
+            if let Some(column) = column {
+                self.add(*cell, Target::Sort(*column));
+            }
+        }
+    }
+
+    /// The target under `(column, row)`. Later targets sit on top of
+    /// earlier ones.
+    pub fn at(&self, column: u16, row: u16) -> Option<Target> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(Position::new(column, row)))
+            .map(|&(_, target)| target)
+    }
+}
+
+/// Where the cells of a header-only table land, laid out the way `Table`
+/// does it.
+pub fn cells(row: Rect, widths: &[Constraint]) -> Rc<[Rect]> {
+    Layout::horizontal(widths)
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(row)
+}
+
+/// Handles a mouse event in `mode`.
+pub fn handle(app: &mut App, mouse: MouseEvent, mode: Mode) -> Step {
+    let target = app.hits.at(mouse.column, mouse.row);
+    match (mouse.kind, target) {
+        (MouseEventKind::Down(MouseButton::Left), Some(Target::Mode(next))) => {
+            return Step::Switch(next);
+        }
+        (MouseEventKind::Down(MouseButton::Left), Some(Target::Action(action))) => {
+            return tui::perform(app, action, mode);
+        }
+        (MouseEventKind::Down(MouseButton::Left), Some(Target::Suggestion(i))) => {
+            search::pick(app, i);
+        }
+        (MouseEventKind::Down(MouseButton::Left), Some(Target::Sort(column))) => {
+            app.sort_on(column);
+        }
+        (MouseEventKind::Down(MouseButton::Left), Some(Target::Setting(n))) => {
+            app.settings.table.select(Some(n));
+        }
+        (MouseEventKind::ScrollDown, Some(Target::Setting(_))) => {
+            app.settings.table.select_next();
+        }
+        (MouseEventKind::ScrollUp, Some(Target::Setting(_))) => {
+            app.settings.table.select_previous();
+        }
+        (MouseEventKind::Down(MouseButton::Left), Some(Target::Task(i))) => {
+            app.selected_index = Some(i);
+            app.focus_textarea = false;
+        }
+        (MouseEventKind::ScrollDown, Some(Target::Task(_) | Target::TaskList)) => {
+            app.select_next();
+        }
+        (MouseEventKind::ScrollUp, Some(Target::Task(_) | Target::TaskList)) => {
+            app.select_prev();
+        }
+        (MouseEventKind::ScrollDown, Some(Target::Logs)) => app.logs_scroll += 1,
+        (MouseEventKind::ScrollUp, Some(Target::Logs)) => {
+            app.logs_scroll = app.logs_scroll.saturating_sub(1);
+        }
+        _ => {}
+    }
+    Step::Stay
+}
@@ -0,0 +1,262 @@
+use crate::config::PomodoroConfig;
+use crate::tracker::{Break, BreakRecord, BreakStatus, Event, TaskId, TaskStatus, Tracker};
+
+use chrono::{DateTime, Local};
+
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhaseKind {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Phase {
+    pub kind: PhaseKind,
+    pub planned: Duration,
+}
+
+/// Number of phases in one full cycle, e.g. W S W S W S W L for the
+/// default 25/5 ×4 then 15.
+pub fn cycle_len(config: &PomodoroConfig) -> usize {
+    2 * config.long_break_every
+}
+
+/// Phase `k` of the cycle, wrapping around: work on even positions, then a
+/// short break, or the long one after every `long_break_every`-th pomodoro.
+pub fn phase(config: &PomodoroConfig, k: usize) -> Phase {
+    let minutes = |m: u64| Duration::from_secs(m * 60);
+    let k = k % cycle_len(config);
+    if k.is_multiple_of(2) {
+        Phase {
+            kind: PhaseKind::Work,
+            planned: minutes(config.work_minutes),
+        }
+    } else if k + 1 == cycle_len(config) {
+        Phase {
+            kind: PhaseKind::LongBreak,
+            planned: minutes(config.long_break_minutes),
+        }
+    } else {
+        Phase {
+            kind: PhaseKind::ShortBreak,
+            planned: minutes(config.short_break_minutes),
+        }
+    }
+}
+
+/// Phase a task is currently in.
+pub fn current_phase(tracker: &Tracker, task_id: TaskId) -> Phase {
+    let k = tracker.breaks.get(&task_id).map_or(0, |b| b.phase);
+    phase(&tracker.pomodoro, k)
+}
+
+fn next_break_index(tracker: &Tracker, task_id: TaskId) -> usize {
+    let config = &tracker.pomodoro;
+    let k = tracker.breaks.get(&task_id).map_or(0, |b| b.phase) % cycle_len(config);
+    if phase(config, k).kind == PhaseKind::Work {
+        (k + 1) % cycle_len(config)
+    } else {
+        k
+    }
+}
+
+/// Break the task gets the next time it is paused.
+pub fn next_break(tracker: &Tracker, task_id: TaskId) -> Phase {
+    phase(&tracker.pomodoro, next_break_index(tracker, task_id))
+}
+
+/// Moves a task that was just paused into the next break of its cycle.
+pub fn start_break(tracker: &mut Tracker, task_id: TaskId) {
+    let k = next_break_index(tracker, task_id);
+    let planned = phase(&tracker.pomodoro, k).planned;
+    let now = tracker.now();
+    let b = tracker.breaks.entry(task_id).or_insert_with(Break::new);
+    if b.started_at.is_some() {
+        return;
+    }
+
+    b.phase = k;
+    b.planned = planned;
+    b.elapsed = Duration::ZERO;
+    b.started_at = Some(now);
+    b.break_status = BreakStatus::Started;
+    tracker.emit(Event::BreakStarted(task_id));
+}
+
+/// Closes the running break of a task that is back at work and records how
+/// long it actually ran against its plan.
+pub fn end_break(tracker: &mut Tracker, task_id: TaskId) {
+    let len = cycle_len(&tracker.pomodoro);
+    let now = tracker.now();
+    let Some(b) = tracker.breaks.get_mut(&task_id) else {
+        return;
+    };
+    let Some(start) = b.started_at.take() else {
+        return;
+    };
+
+    b.elapsed = (now - start).to_std().unwrap_or_default();
+    b.break_status = if b.elapsed > b.planned {
+        BreakStatus::Exceeded
+    } else {
+        BreakStatus::Completed
+    };
+    b.history.push(BreakRecord {
+        planned: b.planned,
+        actual: b.elapsed,
+        ended_at: now,
+    });
+    b.phase = (b.phase + 1) % len;
+    tracker.emit(Event::BreakEnded(task_id));
+}
+
+/// Logs a break that already happened between `start` and `end`, as if the
+/// task had been paused for it.
+pub fn record_break(
+    tracker: &mut Tracker,
+    task_id: TaskId,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) {
+    let k = next_break_index(tracker, task_id);
+    let planned = phase(&tracker.pomodoro, k).planned;
+    let len = cycle_len(&tracker.pomodoro);
+    let b = tracker.breaks.entry(task_id).or_insert_with(Break::new);
+    let actual = (end - start).to_std().unwrap_or_default();
+    b.planned = planned;
+    b.elapsed = actual;
+    b.break_status = if actual > b.planned {
+        BreakStatus::Exceeded
+    } else {
+        BreakStatus::Completed
+    };
+    b.history.push(BreakRecord {
+        planned: b.planned,
+        actual,
+        ended_at: end,
+    });
+    b.phase = (k + 1) % len;
+}
+
+/// Advances every running break. Called once per tick, before drawing.
+pub fn tick(tracker: &mut Tracker) {
+    let now = tracker.now();
+    for task in &tracker.tasks {
+        if !matches!(task.status, TaskStatus::Paused) {
+            continue;
+        }
+        let Some(b) = tracker.breaks.get_mut(&task.id) else {
+            continue;
+        };
+        let Some(start) = b.started_at else {
+            continue;
+        };
+
+        b.elapsed = (now - start).to_std().unwrap_or_default();
+        b.break_status = if b.elapsed > b.planned {
+            BreakStatus::Exceeded
+        } else if b.elapsed >= Duration::from_secs(1) {
+            BreakStatus::Ongoing
+        } else {
+            BreakStatus::Started
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::config::Config;
+    use crate::tracker::TaskSpec;
+
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    fn mins(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    /// A tracker on a mock clock with "Focus" running.
+    fn focus() -> (Tracker, MockClock, TaskId) {
+        let clock = MockClock::new(Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap());
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(clock.clone());
+        tracker.configure(&Config::default());
+        let i = tracker.start(TaskSpec::parse("Focus")).unwrap();
+        let id = tracker.tasks[i].id;
+        (tracker, clock, id)
+    }
+
+    #[test]
+    fn phases_follow_the_configured_cycle() {
+        let config = PomodoroConfig {
+            long_break_every: 3,
+            ..PomodoroConfig::default()
+        };
+        let kinds: Vec<PhaseKind> = (0..8).map(|k| phase(&config, k).kind).collect();
+        use PhaseKind::*;
+        assert_eq!(
+            kinds,
+            vec![
+                Work, ShortBreak, Work, ShortBreak, Work, LongBreak, Work, ShortBreak
+            ]
+        );
+        assert_eq!(phase(&config, 5).planned, mins(15));
+        assert_eq!(phase(&config, 1).planned, mins(5));
+        assert_eq!(phase(&config, 0).planned, mins(25));
+    }
+
+    #[test]
+    fn the_long_break_comes_after_every_fourth_pomodoro() {
+        let (mut tracker, clock, id) = focus();
+        let mut planned = Vec::new();
+        for _ in 0..5 {
+            clock.advance(mins(25));
+            tracker.pause(0, None);
+            planned.push(tracker.breaks[&id].planned);
+            clock.advance(mins(1));
+            tracker.resume(0);
+        }
+        assert_eq!(planned, vec![mins(5), mins(5), mins(5), mins(15), mins(5)]);
+    }
+
+    #[test]
+    fn a_break_records_planned_against_actual() {
+        let (mut tracker, clock, id) = focus();
+        tracker.pause(0, None);
+        clock.advance(mins(3));
+        tick(&mut tracker);
+        assert_eq!(tracker.breaks[&id].break_status, BreakStatus::Ongoing);
+        tracker.resume(0);
+
+        let b = &tracker.breaks[&id];
+        assert_eq!(b.break_status, BreakStatus::Completed);
+        assert_eq!(
+            b.history,
+            vec![BreakRecord {
+                planned: mins(5),
+                actual: mins(3),
+                ended_at: tracker.now(),
+            }]
+        );
+        assert_eq!(b.history[0].overrun(), Duration::ZERO);
+    }
+
+    #[test]
+    fn a_break_that_runs_long_is_exceeded() {
+        let (mut tracker, clock, id) = focus();
+        tracker.pause(0, None);
+        clock.advance(mins(7));
+        tick(&mut tracker);
+        assert_eq!(tracker.breaks[&id].break_status, BreakStatus::Exceeded);
+        tracker.resume(0);
+
+        let b = &tracker.breaks[&id];
+        assert_eq!(b.break_status, BreakStatus::Exceeded);
+        assert_eq!(b.history[0].overrun(), mins(2));
+    }
+}
@@ -18,14 +18,14 @@ pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     ])
     .split(popup_layout[1])[1]
 }
-pub fn pop_up(frame: &mut Frame) {
-    let area = centered_rect(60, 20, frame.size()); // 60% width, 20% height
+pub fn pop_up(frame: &mut Frame, background: Color) {
+    let area = centered_rect(60, 20, frame.area()); // 60% width, 20% height
     frame.render_widget(Clear, area); // Clears the background
     let block = Block::default()
         .title("WARNING")
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .style(Style::default().bg(Color::Red));
+        .style(Style::default().bg(background));
     let paragraph = Paragraph::new("A paused task with the same name already exists!")
         .centered()
         .alignment(Alignment::Center);
@@ -0,0 +1,290 @@
+use crate::tracker::Tracker;
+
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, TimeZone};
+use clap::ValueEnum;
+
+use std::time::Duration;
+
+/// Calendar bucket the report totals are summed into.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// Label of the bucket `date` falls into: `2026-10-18`, `2026-W42` or
+    /// `2026-10`.
+    pub fn key(self, date: NaiveDate) -> String {
+        match self {
+            Period::Day => date.format("%Y-%m-%d").to_string(),
+            Period::Week => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => date.format("%Y-%m").to_string(),
+        }
+    }
+
+    /// Range the Reports view shows: the last 7 days, 4 weeks or 6 months up
+    /// to `today`, moved `offset` whole ranges into the past.
+    pub fn window(self, today: NaiveDate, offset: u32) -> (NaiveDate, NaiveDate) {
+        match self {
+            Period::Day => {
+                let to = today - Days::new(7 * offset as u64);
+                (to - Days::new(6), to)
+            }
+            Period::Week => {
+                let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+                let to = monday - Days::new(28 * offset as u64);
+                (to - Days::new(21), to + Days::new(6))
+            }
+            Period::Month => {
+                let first = today.with_day(1).unwrap_or(today);
+                let to = first - Months::new(6 * offset);
+                (
+                    to - Months::new(5),
+                    (to + Months::new(1)).pred_opt().unwrap_or(to),
+                )
+            }
+        }
+    }
+}
+
+/// What each period total is broken down by.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Breakdown {
+    Task,
+    Project,
+}
+
+/// Time tracked in one period, split by task or project, largest first.
+pub struct PeriodTotal {
+    pub period: String,
+    pub total: Duration,
+    pub entries: Vec<(String, Duration)>,
+}
+
+/// Sums the interval history (plus anything still running) between `from`
+/// and `to`, both inclusive. Stretches that cross midnight count towards the
+/// day each part of them fell on.
+pub fn build(
+    tracker: &Tracker,
+    from: NaiveDate,
+    to: NaiveDate,
+    period: Period,
+    breakdown: Breakdown,
+) -> Vec<PeriodTotal> {
+    let now = tracker.now();
+    let stretches = tracker
+        .intervals
+        .iter()
+        .map(|iv| (iv.task_id, iv.start, iv.end))
+        .chain(
+            tracker
+                .tasks
+                .iter()
+                .filter_map(|t| t.started_at.map(|start| (t.id, start, now))),
+        );
+
+    let mut totals: Vec<PeriodTotal> = Vec::new();
+    for (task_id, start, end) in stretches {
+        let Some(task) = tracker.tasks.iter().find(|t| t.id == task_id) else {
+            continue;
+        };
+        let key = match breakdown {
+            Breakdown::Task => task.name.clone(),
+            Breakdown::Project => task
+                .project
+                .clone()
+                .unwrap_or_else(|| "(no project)".to_string()),
+        };
+
+        for (day, spent) in split_by_day(start, end) {
+            if day < from || day > to {
+                continue;
+            }
+            let label = period.key(day);
+            let bucket = match totals.iter().position(|p| p.period == label) {
+                Some(p) => &mut totals[p],
+                None => {
+                    totals.push(PeriodTotal {
+                        period: label,
+                        total: Duration::ZERO,
+                        entries: Vec::new(),
+                    });
+                    totals.last_mut().unwrap()
+                }
+            };
+            bucket.total += spent;
+            match bucket.entries.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, d)) => *d += spent,
+                None => bucket.entries.push((key.clone(), spent)),
+            }
+        }
+    }
+
+    for bucket in &mut totals {
+        bucket
+            .entries
+            .sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    }
+    totals.sort_by(|a, b| a.period.cmp(&b.period));
+    totals
+}
+
+fn split_by_day(start: DateTime<Local>, end: DateTime<Local>) -> Vec<(NaiveDate, Duration)> {
+    let mut slices = Vec::new();
+    let mut cursor = start;
+    while cursor < end {
+        let day = cursor.date_naive();
+        let midnight = day
+            .succ_opt()
+            .and_then(|next| next.and_hms_opt(0, 0, 0))
+            .and_then(|next| Local.from_local_datetime(&next).earliest())
+            .unwrap_or(end);
+        let slice_end = midnight.min(end);
+        slices.push((day, (slice_end - cursor).to_std().unwrap_or_default()));
+        cursor = slice_end;
+    }
+    slices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::tracker::{TaskSpec, TaskStatus};
+
+    use std::sync::Arc;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(day: NaiveDate, h: u32, m: u32) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&day.and_hms_opt(h, m, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    fn mins(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    /// "Write +docs" and "Email": 1h of writing on Feb 2, 23:30-00:30 on
+    /// Feb 3-4, and 30m of email on Feb 4.
+    fn tracker() -> Tracker {
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(MockClock::new(at(date(2026, 2, 20), 12, 0)));
+        let write = tracker
+            .add_task(TaskSpec::parse("Write +docs"), TaskStatus::Paused)
+            .unwrap();
+        let email = tracker
+            .add_task(TaskSpec::parse("Email"), TaskStatus::Paused)
+            .unwrap();
+        let (write, email) = (tracker.tasks[write].id, tracker.tasks[email].id);
+        let (feb2, feb3, feb4) = (date(2026, 2, 2), date(2026, 2, 3), date(2026, 2, 4));
+        for (id, start, end) in [
+            (write, at(feb2, 9, 0), at(feb2, 10, 0)),
+            (write, at(feb3, 23, 30), at(feb4, 0, 30)),
+            (email, at(feb4, 8, 0), at(feb4, 8, 30)),
+        ] {
+            tracker.add_interval(id, start, end, None).unwrap();
+        }
+        tracker
+    }
+
+    #[test]
+    fn split_by_day_cuts_at_midnight() {
+        let day = date(2026, 2, 3);
+        assert_eq!(
+            split_by_day(at(day, 23, 30), at(date(2026, 2, 5), 0, 15)),
+            vec![
+                (day, mins(30)),
+                (date(2026, 2, 4), mins(24 * 60)),
+                (date(2026, 2, 5), mins(15)),
+            ]
+        );
+        assert_eq!(split_by_day(at(day, 9, 0), at(day, 9, 0)), vec![]);
+    }
+
+    #[test]
+    fn build_sums_per_day_by_task() {
+        let totals = build(
+            &tracker(),
+            date(2026, 2, 3),
+            date(2026, 2, 4),
+            Period::Day,
+            Breakdown::Task,
+        );
+        let summary: Vec<_> = totals
+            .iter()
+            .map(|p| (p.period.as_str(), p.total, p.entries.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "2026-02-03",
+                    mins(30),
+                    vec![("Write".to_string(), mins(30))]
+                ),
+                (
+                    "2026-02-04",
+                    mins(60),
+                    vec![
+                        ("Email".to_string(), mins(30)),
+                        ("Write".to_string(), mins(30))
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_sums_per_week_by_project() {
+        let totals = build(
+            &tracker(),
+            date(2026, 2, 1),
+            date(2026, 2, 28),
+            Period::Week,
+            Breakdown::Project,
+        );
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].period, "2026-W06");
+        assert_eq!(totals[0].total, mins(150));
+        assert_eq!(
+            totals[0].entries,
+            vec![
+                ("docs".to_string(), mins(120)),
+                ("(no project)".to_string(), mins(30))
+            ]
+        );
+    }
+
+    #[test]
+    fn window_covers_whole_periods_and_steps_back() {
+        let today = date(2026, 10, 18); // a Sunday
+        assert_eq!(Period::Day.window(today, 0), (date(2026, 10, 12), today));
+        assert_eq!(
+            Period::Day.window(today, 1),
+            (date(2026, 10, 5), date(2026, 10, 11))
+        );
+        assert_eq!(Period::Week.window(today, 0), (date(2026, 9, 21), today));
+        assert_eq!(
+            Period::Week.window(today, 1),
+            (date(2026, 8, 24), date(2026, 9, 20))
+        );
+        assert_eq!(
+            Period::Month.window(today, 0),
+            (date(2026, 5, 1), date(2026, 10, 31))
+        );
+        assert_eq!(
+            Period::Month.window(today, 1),
+            (date(2025, 11, 1), date(2026, 4, 30))
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use crate::export::{self, Format};
+use crate::keymap::Action;
+use crate::report::{self, Breakdown, Period};
+use crate::tui::{self, Areas, Mode, Step};
+use crate::util::{App, format_hms};
+
+use crossterm::event::KeyEvent;
+
+use ratatui::prelude::*;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Borders, Cell, Padding, Row, Table};
+
+use chrono::NaiveDate;
+
+/// The first and last day the report currently covers.
+fn window(app: &App) -> (NaiveDate, NaiveDate) {
+    app.report_period
+        .window(app.tracker.now().date_naive(), app.report_offset)
+}
+
+/// Draws the range, the report table and the per-period bar chart.
+pub fn draw(frame: &mut Frame, app: &App, areas: Areas) {
+    let colors = app.config.colors;
+    let (from, to) = window(app);
+    let totals = report::build(
+        &app.tracker,
+        from,
+        to,
+        app.report_period,
+        app.report_breakdown,
+    );
+
+    let range = Row::new(vec![Cell::from(format!(
+        "{} → {}",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d")
+    ))])
+    .style(Style::new().fg(colors.highlight));
+
+    frame.render_widget(
+        Table::new(Vec::<Row>::new(), [Constraint::Percentage(100)])
+            .header(range)
+            .block(
+                Block::new()
+                    .title("RANGE")
+                    .title_alignment(Alignment::Center)
+                    .style(Style::new().fg(colors.text))
+                    .borders(Borders::ALL)
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .padding(Padding::new(1, 1, 0, 0)),
+            ),
+        areas.slot,
+    );
+
+    let panel = app.config.layout.chart_panel;
+    let nested_report_data = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![
+            Constraint::Percentage(100 - panel),
+            Constraint::Percentage(panel),
+        ])
+        .split(areas.body);
+
+    // REPORT TABLE
+    let breakdown_str = match app.report_breakdown {
+        Breakdown::Task => "[Task]",
+        Breakdown::Project => "[Project]",
+    };
+    let report_cells = ["[Period]", breakdown_str, "[Time Spent]"]
+        .iter()
+        .map(|h| Cell::from(*h))
+        .collect::<Vec<Cell>>();
+
+    let report_header = Row::new(report_cells).style(Style::new().fg(colors.heading));
+
+    let mut report_rows: Vec<Row> = Vec::new();
+    for period in &totals {
+        report_rows.push(
+            Row::new(vec![
+                Cell::from(period.period.clone()),
+                Cell::from(""),
+                Cell::from(format_hms(period.total)),
+            ])
+            .style(
+                Style::new()
+                    .fg(colors.highlight)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+        report_rows.extend(period.entries.iter().map(|(key, spent)| {
+            Row::new(vec![
+                Cell::from(""),
+                Cell::from(key.clone()),
+                Cell::from(format_hms(*spent)),
+            ])
+        }));
+    }
+
+    let grand_total: std::time::Duration = totals.iter().map(|p| p.total).sum();
+    let period_str = match app.report_period {
+        Period::Day => "DAILY",
+        Period::Week => "WEEKLY",
+        Period::Month => "MONTHLY",
+    };
+
+    frame.render_widget(
+        Table::new(
+            report_rows,
+            [
+                Constraint::Percentage(30),
+                Constraint::Percentage(45),
+                Constraint::Percentage(25),
+            ],
+        )
+        .header(report_header)
+        .style(Style::new().fg(colors.text))
+        .block(
+            Block::new()
+                .title(format!("{} REPORT", period_str))
+                .title_bottom(
+                    Line::from(format!(" Total {} ", format_hms(grand_total))).right_aligned(),
+                )
+                .title_bottom(
+                    Line::from(
+                        app.status_message
+                            .as_ref()
+                            .map(|msg| format!(" {msg} "))
+                            .unwrap_or_default(),
+                    )
+                    .style(Style::new().fg(colors.highlight)),
+                )
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Plain)
+                .padding(Padding::new(1, 1, 1, 1)),
+        ),
+        nested_report_data[0],
+    );
+
+    // PERIOD TOTALS BAR GRAPH
+    let bars: Vec<Bar> = totals
+        .iter()
+        .map(|p| {
+            let minutes = p.total.as_secs() / 60;
+            Bar::default()
+                .value(minutes)
+                .label(Line::from(p.period.clone()))
+                .text_value(format!("{minutes}m"))
+        })
+        .collect();
+
+    let chart = BarChart::default()
+        .block(
+            Block::default()
+                .title("TIME PER PERIOD")
+                .borders(Borders::ALL)
+                .padding(Padding::new(2, 0, 2, 0)),
+        )
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(10)
+        .bar_gap(2)
+        .label_style(Style::default().fg(colors.label))
+        .bar_style(Style::default().fg(colors.highlight))
+        .value_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(colors.highlight)
+                .bold(),
+        );
+
+    frame.render_widget(chart, nested_report_data[1]);
+}
+
+pub fn handle_key(app: &mut App, key: KeyEvent) -> Step {
+    match app.keymap.action(&key, Mode::Reports, false) {
+        Some(action) => perform(app, action),
+        None => Step::Stay,
+    }
+}
+
+/// Runs `action` on the Reports view, for a key press or a MENU click.
+pub fn perform(app: &mut App, action: Action) -> Step {
+    match action {
+        Action::DayReport => {
+            app.report_period = Period::Day;
+            app.report_offset = 0;
+        }
+        Action::WeekReport => {
+            app.report_period = Period::Week;
+            app.report_offset = 0;
+        }
+        Action::MonthReport => {
+            app.report_period = Period::Month;
+            app.report_offset = 0;
+        }
+        Action::Breakdown => {
+            app.report_breakdown = match app.report_breakdown {
+                Breakdown::Task => Breakdown::Project,
+                Breakdown::Project => Breakdown::Task,
+            };
+        }
+        Action::EarlierRange => {
+            app.report_offset += 1;
+        }
+        Action::LaterRange => {
+            app.report_offset = app.report_offset.saturating_sub(1);
+        }
+        action @ (Action::ExportCsv | Action::ExportJson) => {
+            let format = if action == Action::ExportCsv {
+                Format::Csv
+            } else {
+                Format::Json
+            };
+            let dataset = format.default_dataset();
+            let path = export::default_path(format, dataset, app.tracker.now());
+            let (from, to) = window(app);
+            app.status_message = Some(
+                match export::render(&app.tracker, Some(from), Some(to), format, dataset)
+                    .and_then(|contents| export::write(&path, &contents))
+                {
+                    Ok(()) => format!("Exported to {}", path.display()),
+                    Err(err) => format!("Export failed: {err}"),
+                },
+            );
+        }
+        action => {
+            return tui::shared(app, action, Mode::Reports);
+        }
+    }
+    Step::Stay
+}
@@ -0,0 +1,195 @@
+//! Fuzzy matching of task names: the `/` filter that narrows the TASKS
+//! tables as you type, and the suggestions under the textarea that pick an
+//! existing task instead of creating a near-duplicate.
+
+use crate::mouse::Target;
+use crate::tracker::{TaskSpec, TaskStatus, Tracker};
+use crate::util::App;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, List, ListItem};
+use tui_textarea::TextArea;
+
+/// How many suggestions the dropdown shows at most.
+const SUGGESTIONS: usize = 5;
+
+/// State of the `/` filter.
+#[derive(Debug, Default)]
+pub struct Search {
+    /// Whether keys are going into the query.
+    pub active: bool,
+    /// What the TASKS tables are narrowed to; empty shows every task.
+    pub query: String,
+}
+
+/// Scores how well `query` matches `text`. Every character of the query has
+/// to show up in order, ignoring case and spaces; runs of adjacent
+/// characters and matches at the start of a word score higher. `None` when
+/// it does not match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut last: Option<usize> = None;
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let at = (from..text.len()).find(|&i| text[i] == c)?;
+        score += 1;
+        if at > 0 && last == Some(at - 1) {
+            score += 4;
+        }
+        if at == 0 || !text[at - 1].is_alphanumeric() {
+            score += 2;
+        }
+        last = Some(at);
+        from = at + 1;
+    }
+    Some(score)
+}
+
+/// Existing tasks whose names match what is typed, best first.
+pub fn suggestions(tracker: &Tracker, typed: &str) -> Vec<usize> {
+    let name = TaskSpec::parse(typed).name;
+    if name.is_empty() {
+        return Vec::new();
+    }
+    let mut scored: Vec<(u32, usize)> = tracker
+        .tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| Some((fuzzy_score(&name, &t.name)?, i)))
+        .collect();
+    scored.sort_by(|a, b| {
+        b.0.cmp(&a.0).then(
+            tracker.tasks[a.1]
+                .name
+                .len()
+                .cmp(&tracker.tasks[b.1].name.len()),
+        )
+    });
+    scored
+        .into_iter()
+        .take(SUGGESTIONS)
+        .map(|(_, i)| i)
+        .collect()
+}
+
+/// The suggestions for the textarea, unless it is not being typed into.
+fn current(app: &App) -> Vec<usize> {
+    if !app.focus_textarea || app.renaming.is_some() {
+        return Vec::new();
+    }
+    suggestions(&app.tracker, &app.textarea.lines().join(" "))
+}
+
+/// The filter as the TASKS titles show it, with a cursor while typing.
+pub fn title(app: &App) -> String {
+    match (app.search.active, app.search.query.as_str()) {
+        (true, query) => format!(" [/{query}_]"),
+        (false, "") => String::new(),
+        (false, query) => format!(" [/{query}]"),
+    }
+}
+
+/// Handles a key while the `/` query is being typed. The table follows
+/// every change, keeping the selection on a task that is still listed.
+pub fn handle_key(app: &mut App, key: KeyEvent) {
+    match key.code {
+        KeyCode::Esc => {
+            app.search = Search::default();
+        }
+        KeyCode::Enter => app.search.active = false,
+        KeyCode::Backspace if app.search.query.is_empty() => app.search.active = false,
+        KeyCode::Backspace => {
+            app.search.query.pop();
+        }
+        KeyCode::Down => app.select_next(),
+        KeyCode::Up => app.select_prev(),
+        KeyCode::Char(c) => app.search.query.push(c),
+        _ => {}
+    }
+    app.select_by(0);
+}
+
+/// Handles the keys that walk the suggestion list. Returns whether the key
+/// was used; Enter on a highlighted suggestion resumes that task.
+pub fn handle_suggestion_key(app: &mut App, key: KeyEvent) -> bool {
+    let found = current(app);
+    if found.is_empty() {
+        app.suggestion = None;
+        return false;
+    }
+    match (key.code, app.suggestion) {
+        (KeyCode::Down, None) => app.suggestion = Some(0),
+        (KeyCode::Down, Some(n)) => app.suggestion = Some((n + 1).min(found.len() - 1)),
+        (KeyCode::Up, Some(0)) => app.suggestion = None,
+        (KeyCode::Up, Some(n)) => app.suggestion = Some(n - 1),
+        (KeyCode::Enter, Some(n)) => pick(app, found[n.min(found.len() - 1)]),
+        _ => {
+            // Anything else edits the text, which changes the list
+            app.suggestion = None;
+            return false;
+        }
+    }
+    true
+}
+
+/// Resumes task `i` from the suggestions instead of adding a new one. A
+/// task that is already running just gets selected.
+pub fn pick(app: &mut App, i: usize) {
+    match app.tracker.tasks[i].status {
+        TaskStatus::Active => {
+            let name = &app.tracker.tasks[i].name;
+            app.status_message = Some(format!("'{name}' is already running"));
+        }
+        TaskStatus::Paused => app.tracker.resume(i),
+    }
+    app.textarea = TextArea::default();
+    app.selected_index = Some(i);
+    app.focus_textarea = false;
+    app.suggestion = None;
+}
+
+/// Draws the suggestions in a dropdown under `slot`, the textarea's box.
+pub fn draw_suggestions(frame: &mut Frame, app: &mut App, slot: Rect) {
+    let found = current(app);
+    if found.is_empty() {
+        return;
+    }
+    let colors = app.config.colors;
+    let area = Rect {
+        x: slot.x,
+        y: slot.bottom(),
+        width: slot.width,
+        height: found.len() as u16 + 2,
+    }
+    .intersection(frame.area());
+
+    let items: Vec<ListItem> = found
+        .iter()
+        .enumerate()
+        .map(|(n, &i)| {
+            let task = &app.tracker.tasks[i];
+            let item = ListItem::new(format!("{} ({:?})", task.name, task.status));
+            if Some(n) == app.suggestion {
+                item.style(Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD))
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    frame.render_widget(Clear, area);
+    let block = Block::new()
+        .title("EXISTING TASKS <Down> <Enter>")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .style(Style::new().bg(colors.background).fg(colors.text));
+    let inner = block.inner(area);
+    frame.render_widget(List::new(items).block(block), area);
+
+    for (y, &i) in (inner.y..inner.bottom()).zip(&found) {
+        app.hits
+            .add(Rect::new(inner.x, y, inner.width, 1), Target::Suggestion(i));
+    }
+}
@@ -0,0 +1,190 @@
+//! The Settings view: every config value in one table, editable in place.
+//! A change is checked the same way the config file is, applied right away
+//! and written back to the file the config came from.
+
+use crate::config;
+use crate::keymap::{Action, Keymap};
+use crate::mouse::Target;
+use crate::tui::{self, Areas, Mode, Step};
+use crate::util::App;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::{
+    Block, BorderType, Borders, Cell, Padding, Paragraph, Row, Table, TableState,
+};
+
+/// State of the Settings view.
+#[derive(Debug, Default)]
+pub struct Settings {
+    pub table: TableState,
+    /// What is being typed for the selected setting, `None` while browsing.
+    pub input: Option<String>,
+    /// Why the last value typed was turned down.
+    pub error: Option<String>,
+    /// Rows the table fit on the last frame, for paging.
+    pub page: usize,
+}
+
+/// Draws the value being edited in the slot and the settings table.
+pub fn draw(frame: &mut Frame, app: &mut App, areas: Areas) {
+    let colors = app.config.colors;
+    let entries = app.config.entries();
+    let selected = app
+        .settings
+        .table
+        .selected()
+        .unwrap_or(0)
+        .min(entries.len().saturating_sub(1));
+    app.settings.table.select(Some(selected));
+
+    let (title, text) = match &app.settings.input {
+        Some(input) => (
+            format!("EDIT {}", entries[selected].0),
+            Line::from(vec![
+                Span::raw(input.clone()),
+                Span::raw("█").style(Style::new().fg(colors.heading)),
+            ]),
+        ),
+        None => (
+            "CONFIG FILE".to_string(),
+            Line::from(match &app.config_path {
+                Some(path) => path.display().to_string(),
+                None => "none, changes last for this session".to_string(),
+            }),
+        ),
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(
+            Block::new()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .style(Style::new().fg(colors.text))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .padding(Padding::new(1, 1, 0, 0)),
+        ),
+        areas.slot,
+    );
+
+    let footer = match (&app.settings.error, &app.status_message) {
+        (Some(err), _) => Line::from(format!(" {err} ")).style(Style::new().fg(colors.alert)),
+        (None, Some(msg)) => Line::from(format!(" {msg} ")).style(Style::new().fg(colors.success)),
+        (None, None) => Line::from(" Values are TOML: 25, true, \"#00c8b4\", [\"p\"] ")
+            .style(Style::new().fg(colors.muted)),
+    };
+    let block = Block::new()
+        .title(format!(
+            "SETTINGS {}",
+            app.keymap.hint(Action::EditSetting).unwrap_or_default()
+        ))
+        .title_bottom(footer)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .padding(Padding::new(1, 1, 0, 0));
+    let inner = block.inner(areas.body);
+    app.settings.page = (inner.height as usize).saturating_sub(1).max(1);
+
+    let rows: Vec<Row> = entries
+        .into_iter()
+        .map(|(key, value)| Row::new(vec![Cell::from(key), Cell::from(value)]))
+        .collect();
+    let count = rows.len();
+    let table = Table::new(
+        rows,
+        [Constraint::Percentage(30), Constraint::Percentage(70)],
+    )
+    .header(Row::new(vec!["[Setting]", "[Value]"]).style(Style::new().fg(colors.heading)))
+    .style(Style::new().fg(colors.text))
+    .row_highlight_style(
+        Style::new()
+            .fg(colors.highlight)
+            .add_modifier(Modifier::BOLD),
+    )
+    .block(block);
+    frame.render_stateful_widget(table, areas.body, &mut app.settings.table);
+
+    let offset = app.settings.table.offset();
+    for (y, n) in (inner.y + 1..inner.bottom()).zip(offset..count) {
+        app.hits
+            .add(Rect::new(inner.x, y, inner.width, 1), Target::Setting(n));
+    }
+}
+
+pub fn handle_key(app: &mut App, key: KeyEvent) -> Step {
+    if let Some(input) = &mut app.settings.input {
+        match key.code {
+            KeyCode::Esc => {
+                app.settings.input = None;
+                app.settings.error = None;
+            }
+            KeyCode::Enter => {
+                let input = input.clone();
+                let selected = app.settings.table.selected().unwrap_or(0);
+                let Some((key, _)) = app.config.entries().into_iter().nth(selected) else {
+                    return Step::Stay;
+                };
+                match apply(app, &key, &input) {
+                    Ok(msg) => {
+                        app.settings.input = None;
+                        app.settings.error = None;
+                        app.status_message = Some(msg);
+                    }
+                    Err(err) => app.settings.error = Some(err),
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+        return Step::Stay;
+    }
+    match app.keymap.action(&key, Mode::Settings, false) {
+        Some(action) => perform(app, action),
+        None => Step::Stay,
+    }
+}
+
+/// Runs `action` on the Settings view, for a key press or a MENU click.
+pub fn perform(app: &mut App, action: Action) -> Step {
+    let table = &mut app.settings.table;
+    let page = app.settings.page;
+    match action {
+        Action::NextTask => table.select_next(),
+        Action::PrevTask => table.select_previous(),
+        Action::PageDown => table.scroll_down_by(page as u16),
+        Action::PageUp => table.scroll_up_by(page as u16),
+        Action::Top => table.select_first(),
+        Action::Bottom => table.select_last(),
+        Action::EditSetting => {
+            let selected = table.selected().unwrap_or(0);
+            app.settings.input = app
+                .config
+                .entries()
+                .into_iter()
+                .nth(selected)
+                .map(|(_, value)| value);
+            app.settings.error = None;
+            app.status_message = None;
+        }
+        action => return tui::shared(app, action, Mode::Settings),
+    }
+    Step::Stay
+}
+
+/// Sets `key` to `input` for the running session and saves the config.
+/// Nothing changes when the value does not pass.
+fn apply(app: &mut App, key: &str, input: &str) -> Result<String, String> {
+    let config = app.config.with(key, input)?;
+    app.keymap = Keymap::new(&config.keys)?;
+    app.tracker.configure(&config);
+    app.config = config;
+    let Some(path) = &app.config_path else {
+        return Ok(format!("Changed {key} for this session"));
+    };
+    config::save(path, &app.config)
+        .map_err(|err| format!("{key} is changed but not saved: {err}"))?;
+    Ok(format!("Saved {key} to {}", path.display()))
+}
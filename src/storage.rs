@@ -0,0 +1,198 @@
+use crate::tracker::{Break, Interval, Task, TaskId, Tracker, Trashed};
+
+use serde::{Deserialize, Serialize};
+
+use chrono::{DateTime, Local};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+#[derive(Serialize, Deserialize)]
+struct Store {
+    next_number: u32,
+    tasks: Vec<Task>,
+    #[serde(default)]
+    intervals: Vec<Interval>,
+    #[serde(default)]
+    breaks: HashMap<TaskId, Break>,
+    #[serde(default)]
+    trash: Vec<Trashed>,
+}
+
+/// Location of the task store: `$XDG_DATA_HOME/chronos/tasks.json`.
+pub fn data_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("chronos")
+        .join("tasks.json")
+}
+
+/// Loads the saved tasks into a fresh `Tracker`. A missing file is not an
+/// error, it just means nothing has been tracked yet.
+pub fn load(path: &Path) -> Result<Tracker, Box<dyn std::error::Error>> {
+    let mut tracker = Tracker::default();
+    if !path.exists() {
+        return Ok(tracker);
+    }
+
+    let store: Store = serde_json::from_str(&fs::read_to_string(path)?)?;
+    tracker.next_number = store.next_number;
+    tracker.tasks = store.tasks;
+    tracker.intervals = store.intervals;
+    tracker.intervals.sort_by_key(|iv| iv.start);
+    tracker.breaks = store.breaks;
+    tracker.trash = store.trash;
+
+    Ok(tracker)
+}
+
+/// Writes the tasks, session log and break state of `tracker` to `path`,
+/// replacing the old file only once the new one has been fully written.
+pub fn save(path: &Path, tracker: &Tracker) -> Result<(), Box<dyn std::error::Error>> {
+    let store = Store {
+        next_number: tracker.next_number,
+        tasks: tracker.tasks.clone(),
+        intervals: tracker.intervals.clone(),
+        breaks: tracker.breaks.clone(),
+        trash: tracker.trash.clone(),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(&store)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// When the store file was last modified and how long it was, `None` while
+/// there is no file. Another `chronos` writing the file changes it.
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// The store file as one TUI session knows it, so the session can save as
+/// it goes without writing over what `chronos start` and friends saved in
+/// the meantime.
+pub struct Autosave {
+    path: PathBuf,
+    /// The file as this session last read or wrote it.
+    stamp: Stamp,
+    /// `History::version` of the tracker last read or written.
+    pub version: u64,
+    /// When this session last wrote the file.
+    pub saved_at: Option<DateTime<Local>>,
+}
+
+impl Autosave {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            stamp: None,
+            version: 0,
+            saved_at: None,
+        }
+    }
+
+    /// Loads the file, remembering it as this session's starting point.
+    pub fn load(&mut self) -> Result<Tracker, Box<dyn std::error::Error>> {
+        self.stamp = stamp(&self.path);
+        load(&self.path)
+    }
+
+    /// Whether someone else wrote the file since this session read or
+    /// wrote it.
+    pub fn changed_on_disk(&self) -> bool {
+        stamp(&self.path) != self.stamp
+    }
+
+    /// Writes `tracker` unless the file changed on disk, which would lose
+    /// the other writer's changes.
+    pub fn save(&mut self, tracker: &Tracker) -> Result<(), Box<dyn std::error::Error>> {
+        if self.changed_on_disk() {
+            return Err(format!(
+                "{} changed on disk, not saving over it",
+                self.path.display()
+            )
+            .into());
+        }
+        save(&self.path, tracker)?;
+        self.stamp = stamp(&self.path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::{TaskSpec, TaskStatus};
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("chronos-{}-{name}", std::process::id()))
+            .join("tasks.json")
+    }
+
+    #[test]
+    fn task_ids_survive_a_save_and_load() {
+        let mut tracker = Tracker::default();
+        for name in ["A", "B"] {
+            tracker
+                .add_task(TaskSpec::parse(name), TaskStatus::Paused)
+                .unwrap();
+        }
+        tracker.resume(1);
+        tracker.pause(1, None);
+        tracker.delete(0);
+
+        let path = scratch("roundtrip");
+        save(&path, &tracker).unwrap();
+        let loaded = load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded.tasks, tracker.tasks);
+        assert_eq!(loaded.intervals, tracker.intervals);
+        assert_eq!(loaded.breaks, tracker.breaks);
+        assert_eq!(loaded.trash, tracker.trash);
+        assert_eq!(loaded.next_number, 3);
+    }
+
+    #[test]
+    fn autosave_refuses_to_write_over_changes_made_elsewhere() {
+        let path = scratch("autosave");
+        save(&path, &Tracker::default()).unwrap();
+        let mut session = Autosave::new(path.clone());
+        let mut tracker = session.load().unwrap();
+        tracker
+            .add_task(TaskSpec::parse("Mine"), TaskStatus::Paused)
+            .unwrap();
+        assert!(!session.changed_on_disk());
+        session.save(&tracker).unwrap();
+        assert!(!session.changed_on_disk());
+
+        let mut elsewhere = load(&path).unwrap();
+        elsewhere
+            .add_task(TaskSpec::parse("Theirs"), TaskStatus::Active)
+            .unwrap();
+        save(&path, &elsewhere).unwrap();
+        assert!(session.changed_on_disk());
+        assert!(session.save(&tracker).is_err());
+
+        let names: Vec<_> = session
+            .load()
+            .unwrap()
+            .tasks
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert!(!session.changed_on_disk());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(names, ["Mine", "Theirs"]);
+    }
+}
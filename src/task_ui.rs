@@ -1,10 +1,13 @@
-use crate::redundancy_warning::pop_up;
-use crate::timer::run_timer;
-use crate::util::{ App, Break, Task, TaskStatus };
+use crate::command::{ self, Outcome };
+use crate::keymap::Action;
+use crate::search;
+use crate::tracker::{ TaskSpec, TaskStatus };
+use crate::tui::{ self, Areas, Mode, Step };
+use crate::util::{ format_hms, App, GroupBy, SortBy };
 
-use color_eyre::Result;
+use std::time::Duration;
 
-use crossterm::event::{ self, Event, KeyCode, KeyEventKind };
+use crossterm::event::{ KeyCode, KeyEvent };
 
 use ratatui::prelude::*;
 use ratatui::text::Span;
@@ -23,553 +26,752 @@ use ratatui::widgets::{
     Row,
     Table,
 };
-use ratatui::{ style::{ Color, Modifier, Style }, DefaultTerminal };
+use ratatui::style::{ Color, Modifier, Style };
 
-use std::time::{ Duration, Instant };
 use tui_textarea::TextArea;
 
-pub fn run(mut terminal: DefaultTerminal, mut app: App) -> Result<(), Box<dyn std::error::Error>> {
-    loop {
-        terminal.draw(|frame| {
-            let layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(vec![Constraint::Percentage(100)])
-                .split(frame.area());
-
-            frame.render_widget(
-                Block::default().style(Style::default().bg(Color::Rgb(10, 14, 32))),
-                layout[0]
-            );
-
-            let task_top = Layout::default()
-                .direction(Direction::Vertical)
-                .margin(0)
-                .constraints(
+/// Draws the TASKS table, STATUS panel, bar graph and COMMAND bar.
+pub fn draw(frame: &mut Frame, app: &mut App, areas: Areas) {
+    let colors = app.config.colors;
+    app.textarea.set_block(
+        Block::new()
+            .title(if app.renaming.is_some() { "RENAME TASK" } else { "EDIT TASK" })
+            .title_alignment(Alignment::Center)
+            .style(Style::new().fg(colors.text))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::new(1, 1, 0, 0))
+    );
+    frame.render_widget(&app.textarea, areas.slot);
+
+    // Task table and status on top, the command bar below
+    let body = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(6, 7), Constraint::Ratio(1, 7)])
+        .split(areas.body);
+
+    // TASK TABLE HEADER
+    let panel = app.config.layout.status_panel;
+    let nested_task_data = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(100 - panel), Constraint::Percentage(panel)])
+        .split(body[0]);
+
+    let task_cells = vec![
+        Cell::from(app.column_title("ID", SortBy::Id)),
+        Cell::from(app.column_title("Task Name", SortBy::Name)),
+        Cell::from(app.column_title("Status", SortBy::Status)),
+        Cell::from(app.column_title("Time Spent", SortBy::Time)),
+        Cell::from("[Estimate]")
+    ];
+
+    let task_header = Row::new(task_cells).style(Style::new().fg(colors.heading));
+
+    let mut task_rows: Vec<Row> = Vec::new();
+    // The task behind each row, for the mouse; group headers have none
+    let mut row_tasks: Vec<Option<usize>> = Vec::new();
+    for group in app.task_groups() {
+        // Group header with the subtotal of its tasks
+        if app.group_by != GroupBy::None {
+            task_rows.push(
+                Row::new(
                     vec![
-                        Constraint::Percentage(10), // Header (not focusable)
-                        Constraint::Percentage(10), // Nav tabs (focusable row 0)
-                        Constraint::Percentage(60), // Task table (focusable row 1)
-                        Constraint::Percentage(10), // Command bar (focusable row 2)
-                        Constraint::Percentage(10) // Footer (focusable row 3)
+                        Cell::from(""),
+                        Cell::from(group.key.clone()),
+                        Cell::from(match group.tasks.len() {
+                            1 => "1 task".to_string(),
+                            n => format!("{n} tasks"),
+                        }),
+                        Cell::from(format_hms(group.total))
                     ]
+                ).style(
+                    Style::new().fg(colors.heading).add_modifier(Modifier::BOLD)
                 )
-                .split(layout[0]);
-
-            // HEADER (Not focusable)
-            let header_cells = vec!["[Welcome to Chronos]", "[Mode: Tasks]"];
-            let header = Row::new(
-                header_cells
-                    .iter()
-                    .map(|h| Cell::from(*h))
-                    .collect::<Vec<_>>()
-            ).style(Style::new().fg(Color::Rgb(102, 217, 239)));
-
-            let header_widths = [Constraint::Percentage(90), Constraint::Percentage(10)];
-            frame.render_widget(
-                Table::new(Vec::<Row>::new(), header_widths)
-                    .header(header)
-                    .style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                    .block(
-                        Block::new()
-                            .title(
-                                Span::styled(
-                                    "Chronos",
-                                    Style::new()
-                                        .fg(Color::Rgb(255, 165, 0)) // change title color
-                                        .add_modifier(Modifier::BOLD)
-                                )
-                            )
-                            .title_alignment(Alignment::Center)
-                            .borders(Borders::ALL)
-                            .border_type(ratatui::widgets::BorderType::Plain)
-                            .padding(Padding::new(1, 1, 1, 1))
-                    ),
-                task_top[0]
             );
-
-            //NAVIGATION TABS (Focusable row 0)
-            let task_layout = Layout::default()
-                .direction(Direction::Horizontal)
-                .margin(0)
-                .constraints(vec![Constraint::Percentage(20), Constraint::Percentage(80)])
-                .split(task_top[1]);
-
-            app.textarea.set_block(
-                Block::new()
-                    .title("EDIT TASK")
-                    .title_alignment(Alignment::Center)
-                    .style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .padding(Padding::new(1, 1, 0, 0))
+            row_tasks.push(None);
+        }
+        row_tasks.extend(group.rows.iter().map(|&(i, _)| Some(i)));
+
+        task_rows.extend(group.rows.iter().map(|&(i, depth)| {
+            let task = &app.tracker.tasks[i];
+            let status_str = match task.status {
+                TaskStatus::Active => "Active",
+                TaskStatus::Paused => "Paused",
+            };
+
+            // Parents count their subtasks' time too
+            let elapsed = app.tracker.rolled_up_time(task);
+
+            let marker = if app.tracker.children(task.id).is_empty() {
+                if depth > 0 { "└ " } else { "" }
+            } else if app.collapsed.contains(&task.id) {
+                "▸ "
+            } else {
+                "▾ "
+            };
+            let name_str = format!("{}{marker}{}", "  ".repeat(depth), task.label());
+
+            let time_str = format_hms(elapsed);
+
+            let progress = (elapsed.as_secs_f64() / task.expected_duration.as_secs_f64()) *
+            100.0;
+            let estimate_str = format!(
+                "{} ({:.0}%)",
+                format_hms(task.expected_duration),
+                progress
             );
-            frame.render_widget(&app.textarea, task_layout[0]);
 
-            let nav_cells = vec!["[Tasks]", "|", "[Timer]"];
-            let nav_cells_spans: Vec<Span> = nav_cells
-                .iter()
-                .map(|h| Span::raw(*h))
-                .collect();
-
-            let nav = Row::new(nav_cells_spans).style(Style::new().fg(Color::Rgb(255, 165, 0)));
-
-            frame.render_widget(
-                Table::new(Vec::<Row>::new(), [
-                    Constraint::Percentage(5),
-                    Constraint::Percentage(5),
-                    Constraint::Percentage(5),
-                ])
-                    .header(nav)
-                    .style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                    .block(
-                        Block::new()
-                            .title("MODES")
-                            .borders(Borders::ALL)
-                            .border_type(ratatui::widgets::BorderType::Plain)
-                            .padding(Padding::new(1, 1, 1, 1))
-                    ),
-                task_layout[1]
+            let mut row = Row::new(
+                vec![
+                    Cell::from(task.number.to_string()),
+                    Cell::from(name_str),
+                    Cell::from(status_str),
+                    Cell::from(time_str),
+                    Cell::from(estimate_str)
+                ]
             );
 
-            // TASK TABLE HEADER
-            let nested_task_data = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints(vec![Constraint::Percentage(70), Constraint::Percentage(30)])
-                .split(task_top[2]);
+            // Over budget → flag the whole row
+            if app.tracker.over_budget(task).is_some() {
+                row = row.style(Style::default().fg(colors.alert));
+            }
 
-            let task_cells = vec!["[ID]", "[Task Name]", "[Status]", "[Time Spent]"]
-                .iter()
-                .map(|h| Cell::from(*h))
-                .collect::<Vec<Cell>>();
+            if Some(i) == app.selected_index {
+                row = row.style(
+                    Style::default().fg(colors.highlight).add_modifier(Modifier::BOLD)
+                );
+            }
 
-            let task_header = Row::new(task_cells).style(Style::new().fg(Color::Rgb(255, 165, 0)));
+            row
+        }));
+    }
 
-            let task_rows: Vec<Row> = app.tasks
-                .iter()
-                .enumerate()
-                .map(|(i, task)| {
-                    let status_str = match task.status {
-                        TaskStatus::Active => "Active",
-                        TaskStatus::Paused => "Paused",
-                    };
-
-                    // If active, calculate elapsed time dynamically
-                    let elapsed = if let Some(started) = task.started_at {
-                        task.time_spent + started.elapsed()
+    let task_widths = [
+        Constraint::Percentage(10),
+        Constraint::Percentage(30),
+        Constraint::Percentage(15),
+        Constraint::Percentage(20),
+        Constraint::Percentage(25),
+    ];
+
+    let hints = |actions: &[Action]| {
+        actions
+            .iter()
+            .filter_map(|&a| app.keymap.hint(a))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let tasks_title = match (app.group_by, &app.group_filter) {
+        (GroupBy::None, _) =>
+            format!(
+                "TASKS {}",
+                hints(&[Action::Search, Action::GroupBy, Action::Collapse, Action::Sort])
+            ),
+        (group_by, None) =>
+            format!(
+                "TASKS [by {group_by:?}] {}",
+                hints(&[Action::Search, Action::GroupBy, Action::Filter, Action::Collapse, Action::Sort])
+            ),
+        (group_by, Some(key)) =>
+            format!(
+                "TASKS [by {group_by:?}: {key}] {}",
+                hints(&[Action::Search, Action::GroupBy, Action::Filter, Action::Collapse, Action::Sort])
+            ),
+    };
+
+    let (estimated, actual) = app.tracker.estimate_summary();
+    let summary = format!(
+        " Estimated {} | Actual {} ",
+        format_hms(estimated),
+        format_hms(actual)
+    );
+
+    let visible = app.visible_tasks();
+    let position = format!(
+        " {}/{} ",
+        app.selected_position(&visible).map_or(0, |p| p + 1),
+        visible.len()
+    );
+
+    let tasks_block = Block::new()
+        .title(format!("{tasks_title}{}", search::title(app)))
+        .title_bottom(Line::from(position).left_aligned())
+        .title_bottom(Line::from(summary).right_aligned())
+        .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
+        .border_type(ratatui::widgets::BorderType::Plain)
+        .padding(Padding::new(1, 1, 1, 1));
+    let tasks_inner = tasks_block.inner(nested_task_data[0]);
+
+    // Scroll so the selected row stays in view
+    app.task_table.select(
+        app.selected_index.and_then(|sel| row_tasks.iter().position(|r| *r == Some(sel)))
+    );
+    frame.render_stateful_widget(
+        Table::new(task_rows, task_widths)
+            .header(task_header)
+            .style(Style::new().fg(colors.text))
+            .block(tasks_block),
+        nested_task_data[0],
+        &mut app.task_table
+    );
+    app.hits.add_rows(tasks_inner, app.task_table.offset(), &row_tasks);
+    app.hits.add_headers(
+        tasks_inner,
+        &task_widths,
+        &[Some(SortBy::Id), Some(SortBy::Name), Some(SortBy::Status), Some(SortBy::Time), None]
+    );
+    app.task_page = (tasks_inner.height as usize).saturating_sub(1).max(1);
+
+    //Task-status Panel
+    let task_status_panel_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(nested_task_data[1]);
+
+    // One clear "currently tracking" line on the STATUS panel
+    let tracking = match app.tracker.running().as_slice() {
+        [] =>
+            Line::from(" ○ Not tracking ").style(Style::new().fg(colors.muted)),
+        [i] =>
+            Line::from(
+                format!(
+                    " ● Tracking {} {} ",
+                    app.tracker.tasks[*i].name,
+                    format_hms(app.tracker.time_spent(&app.tracker.tasks[*i]))
+                )
+            ).style(Style::new().fg(colors.success).add_modifier(Modifier::BOLD)),
+        many =>
+            Line::from(format!(" ● Tracking {} tasks ", many.len())).style(
+                Style::new().fg(colors.success).add_modifier(Modifier::BOLD)
+            ),
+    };
+
+    if let Some(i) = app.selected_index {
+        let task = &app.tracker.tasks[i];
+        let elapsed = app.tracker.time_spent(task);
+
+        let time_str = if app.tracker.children(task.id).is_empty() {
+            format_hms(elapsed)
+        } else {
+            format!(
+                "{} (Σ {})",
+                format_hms(elapsed),
+                format_hms(app.tracker.rolled_up_time(task))
+            )
+        };
+
+        let mut items = vec![
+            ListItem::new("[TASK DETAILS]
+-------------- ").style(
+                Style::new().fg(colors.heading)
+            ),
+            ListItem::new(format!("Task: {}", app.tracker.task_path(task))).style(
+                Style::new().fg(colors.highlight)
+            ),
+            ListItem::new(format!("Status: {:?}", task.status)).style(
+                Style::new().fg(colors.success)
+            ),
+            ListItem::new(format!("Time: {}", time_str)).style(
+                Style::new().fg(colors.info)
+            ),
+            ListItem::new(
+                format!(
+                    "Project: {}  Tags: {}",
+                    task.project.as_deref().unwrap_or("-"),
+                    if task.tags.is_empty() {
+                        "-".to_string()
                     } else {
-                        task.time_spent
-                    };
-
-                    let time_str = {
-                        let secs = elapsed.as_secs();
-                        format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
-                    };
-
-                    let mut row = Row::new(
-                        vec![
-                            Cell::from(task.id.to_string()),
-                            Cell::from(task.name.clone()),
-                            Cell::from(status_str),
-                            Cell::from(time_str)
-                        ]
-                    );
-
-                    if Some(i) == app.selected_index {
-                        row = row.style(
-                            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
-                        );
+                        task.tags.join(", ")
                     }
-
-                    row
-                })
-                .collect();
-
-            let task_widths = [
-                Constraint::Percentage(25),
-                Constraint::Percentage(25),
-                Constraint::Percentage(25),
-                Constraint::Percentage(25),
-            ];
-
-            frame.render_widget(
-                Table::new(task_rows, task_widths)
-                    .header(task_header)
-                    .style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                    .block(
-                        Block::new()
-                            .title("TASKS")
-                            .borders(
-                                Borders::TOP | Borders::LEFT | Borders::RIGHT | Borders::BOTTOM
-                            )
-                            .border_type(ratatui::widgets::BorderType::Plain)
-                            .padding(Padding::new(1, 1, 1, 1))
+                )
+            ).style(Style::new().fg(colors.info)),
+            ListItem::new(format!("Estimate: {}", format_hms(task.expected_duration))).style(
+                Style::new().fg(colors.info)
+            ),
+            match app.tracker.over_budget(task) {
+                Some(over) =>
+                    ListItem::new(format!("Over estimate by {}", format_hms(over))).style(
+                        Style::new().fg(Color::Black).bg(colors.alert).add_modifier(Modifier::BOLD)
                     ),
-                nested_task_data[0]
+                None => ListItem::new(""),
+            }
+        ];
+
+        // Latest logged intervals, numbered for :edit / :split / :unlog
+        let logged: Vec<usize> = (0..app.tracker.intervals.len())
+            .rev()
+            .filter(|&n| app.tracker.intervals[n].task_id == task.id)
+            .take(5)
+            .collect();
+        if !logged.is_empty() {
+            items.push(
+                ListItem::new("Intervals:").style(Style::new().fg(colors.heading))
             );
-
-            //Task-status Panel
-            let task_status_panel_layout = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(vec![Constraint::Percentage(30), Constraint::Percentage(70)])
-                .split(nested_task_data[1]);
-
-            if let Some(i) = app.selected_index {
-                let task = &app.tasks[i];
-                let elapsed = if let Some(started) = task.started_at {
-                    task.time_spent + started.elapsed()
-                } else {
-                    task.time_spent
-                };
-
-                let time_str = {
-                    let secs = elapsed.as_secs();
-                    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
-                };
-
-                let items = vec![
-                    ListItem::new(format!("[TASK DETAILS]
--------------- ")).style(
-                        Style::new().fg(Color::Rgb(255, 165, 0))
-                    ),
-                    ListItem::new(format!("Task: {}", task.name)).style(
-                        Style::new().fg(Color::Yellow)
-                    ),
-                    ListItem::new(format!("Status: {:?}", task.status)).style(
-                        Style::new().fg(Color::Rgb(0, 200, 83))
-                    ),
-                    ListItem::new(format!("Time: {}", time_str)).style(
-                        Style::new().fg(Color::Rgb(173, 216, 230))
+        }
+        for n in logged {
+            let iv = &app.tracker.intervals[n];
+            let overlapping = !app.tracker.overlaps_with(n).is_empty();
+            items.push(
+                ListItem::new(
+                    format!(
+                        "#{} {} {}{}",
+                        n + 1,
+                        iv.span(),
+                        format_hms(iv.duration()),
+                        if overlapping { " overlaps!" } else { "" }
                     )
-                ];
-                let p_pause = Paragraph::new(
-                    Text::from(
-                        Span::raw("[Press <P> to Pause a task]")
-                            .style(Style::new().fg(Color::Red))
-                            .add_modifier(Modifier::BOLD)
+                ).style(
+                    if overlapping {
+                        Style::new().fg(colors.alert).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::new().fg(colors.info)
+                    }
+                )
+            );
+        }
+        let p_pause = Paragraph::new(
+            Text::from(
+                Span::raw(
+                    format!(
+                        "[Press {} to Pause a task]",
+                        app.keymap.key(Action::Pause).unwrap_or_default()
                     )
-                );
-
-                let p_resume = Paragraph::new(
-                    Text::from(
-                        Span::raw("[Press <R> to Resume a task]")
-                            .style(Style::new().fg(Color::Rgb(34, 139, 34)))
-                            .add_modifier(Modifier::BOLD)
+                )
+                    .style(Style::new().fg(colors.alert))
+                    .add_modifier(Modifier::BOLD)
+            )
+        );
+
+        let p_resume = Paragraph::new(
+            Text::from(
+                Span::raw(
+                    format!(
+                        "[Press {} to Resume a task]",
+                        app.keymap.key(Action::Resume).unwrap_or_default()
+                    )
+                )
+                    .style(Style::new().fg(colors.success))
+                    .add_modifier(Modifier::BOLD)
+            )
+        );
+
+        //Rendering bar charts
+        let mut bars: Vec<Bar> = Vec::new();
+        let block = Block::default()
+            .title(
+                if app.group_by != GroupBy::None {
+                    "ELAPSED TIME BAR GRAPH".to_string()
+                } else {
+                    format!(
+                        "ELAPSED TIME BAR GRAPH [{}] {} {}",
+                        if app.graph_leaves { "leaves" } else { "rolled up" },
+                        app.keymap.key(Action::GraphLeaves).unwrap_or_default(),
+                        if app.graph_leaves { "Rolled up" } else { "Leaves" }
                     )
-                );
-
-                //Rendering bar charts
-                let mut bars: Vec<Bar> = Vec::new();
-                let block = Block::default()
-                    .title("ELAPSED TIME BAR GRAPH")
-                    .borders(Borders::ALL)
-                    .padding(Padding::new(2, 0, 5, 0));
-
-                for task in &app.tasks {
-                    let elapsed = if let Some(started) = task.started_at {
-                        task.time_spent + started.elapsed()
-                    } else {
-                        task.time_spent
-                    };
-
-                    let minutes = (elapsed.as_secs() / 60) as u64;
-
-                    bars.push(
-                        Bar::default()
-                            .value(minutes)
-                            .label(Line::from(task.name.as_str()))
-                            .text_value(format!("{minutes}m"))
-                    );
                 }
-                // Put bars into a group
-                let group = BarGroup::default().bars(&bars);
-
-                let chart = BarChart::default()
-                    .block(block)
-                    .data(group)
-                    .bar_width(5) // width of each bar (columns)
-                    .bar_gap(3) // gap between bars in the same group
-                    .group_gap(3) // gap between groups (useful for multi-series)
-                    .max(20) // top of the scale (choose >= max value)
-                    .label_style(Style::default().fg(Color::Rgb(102, 217, 239)))
-                    .bar_style(Style::default().fg(Color::Yellow))
-                    .value_style(Style::default().fg(Color::Black).bg(Color::Yellow).bold());
-
-                frame.render_widget(
-                    List::new(items).block(
-                        //.style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                        Block::new()
-                            .title("STATUS")
-                            .borders(
-                                Borders::TOP | Borders::LEFT | Borders::RIGHT | Borders::BOTTOM
-                            )
-                            .border_type(ratatui::widgets::BorderType::Thick)
-                            .padding(Padding::new(2, 2, 1, 1))
-                    ),
-                    task_status_panel_layout[0]
-                );
-                frame.render_widget(
-                    p_pause
-                        .block(
-                            Block::default()
-                                .title("STATUS")
-                                .borders(Borders::ALL)
-                                .padding(Padding::new(0, 2, 5, 0))
-                        )
-                        .alignment(Alignment::Right),
-                    task_status_panel_layout[0]
-                );
-                frame.render_widget(
-                    p_resume
-                        .block(
-                            Block::default()
-                                .title("STATUS")
-                                .borders(Borders::ALL)
-                                .padding(Padding::new(0, 2, 0, 5))
-                        )
-                        .alignment(Alignment::Right),
-                    task_status_panel_layout[0]
-                );
-                frame.render_widget(chart, task_status_panel_layout[1]);
-            } else {
-                // No task selected → show empty state
-                let list = List::new(vec![ListItem::new("Task list")]).block(
-                    Block::default().title("STATUS").borders(Borders::ALL)
-                );
-
-                frame.render_widget(list, task_status_panel_layout[0]);
-
-                let stat_list = List::new(vec![ListItem::new("All task Stats")]).block(
-                    Block::default().title("ELAPSED TIME BAR GRAPH").borders(Borders::ALL)
-                );
-
-                frame.render_widget(stat_list, task_status_panel_layout[1]);
-            }
-
-            // COMMAND BAR (Focusable row 2)
-            let command_cells = vec!["Command: "];
-            let command_cells_spans: Vec<Span> = command_cells
+            )
+            .borders(Borders::ALL)
+            .padding(Padding::new(2, 0, 2, 0));
+
+        // One bar per group when grouped. Otherwise one per top-level
+        // task with its subtasks rolled in, or one per leaf task; a
+        // parent's own time shows up among the leaves too.
+        let totals: Vec<(String, Duration)> = if app.group_by != GroupBy::None {
+            app.task_groups()
+                .into_iter()
+                .map(|g| (g.key, g.total))
+                .collect()
+        } else if app.graph_leaves {
+            app.tracker.tasks
                 .iter()
-                .map(|h| Span::raw(*h))
-                .collect();
-
-            let command_header = Row::new(command_cells_spans).style(
-                Style::new().fg(Color::Rgb(102, 217, 239))
-            );
-
-            frame.render_widget(
-                Table::new(Vec::<Row>::new(), [Constraint::Percentage(90)])
-                    .header(command_header)
-                    .style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                    .block(
-                        Block::new()
-                            .title("COMMAND")
-                            .borders(Borders::ALL)
-                            .border_type(ratatui::widgets::BorderType::Plain)
-                            .padding(Padding::new(1, 1, 1, 1))
-                    ),
-                task_top[3]
+                .filter(|t| {
+                    app.tracker.children(t.id).is_empty() || !app.tracker.time_spent(t).is_zero()
+                })
+                .map(|t| (t.name.clone(), app.tracker.time_spent(t)))
+                .collect()
+        } else {
+            app.task_groups()
+                .into_iter()
+                .flat_map(|g| g.rows)
+                .filter(|&(_, depth)| depth == 0)
+                .map(|(i, _)| (app.tracker.tasks[i].name.clone(), app.tracker.rolled_up_time(&app.tracker.tasks[i])))
+                .collect()
+        };
+
+        for (label, elapsed) in totals {
+            let minutes = elapsed.as_secs() / 60;
+
+            bars.push(
+                Bar::default()
+                    .value(minutes)
+                    .label(Line::from(label))
+                    .text_value(format!("{minutes}m"))
             );
+        }
+        // Put bars into a group
+        let group = BarGroup::default().bars(&bars);
+
+        let chart = BarChart::default()
+            .block(block)
+            .data(group)
+            .bar_width(5) // width of each bar (columns)
+            .bar_gap(3) // gap between bars in the same group
+            .group_gap(3) // gap between groups (useful for multi-series)
+            .max(app.config.ui.chart_max_minutes) // top of the scale
+            .label_style(Style::default().fg(colors.label))
+            .bar_style(Style::default().fg(colors.highlight))
+            .value_style(Style::default().fg(Color::Black).bg(colors.highlight).bold());
+
+        frame.render_widget(
+            List::new(items).block(
+                //.style(Style::new().fg(Color::Rgb(0, 200, 180)))
+                Block::new()
+                    .title("STATUS")
+                    .borders(
+                        Borders::TOP | Borders::LEFT | Borders::RIGHT | Borders::BOTTOM
+                    )
+                    .border_type(ratatui::widgets::BorderType::Thick)
+                    .padding(Padding::new(2, 2, 1, 1))
+            ),
+            task_status_panel_layout[0]
+        );
+        frame.render_widget(
+            p_pause
+                .block(
+                    Block::default()
+                        .title("STATUS")
+                        .borders(Borders::ALL)
+                        .padding(Padding::new(0, 2, 5, 0))
+                )
+                .alignment(Alignment::Right),
+            task_status_panel_layout[0]
+        );
+        frame.render_widget(
+            p_resume
+                .block(
+                    Block::default()
+                        .title("STATUS")
+                        .title(tracking.right_aligned())
+                        .borders(Borders::ALL)
+                        .padding(Padding::new(0, 2, 0, 5))
+                )
+                .alignment(Alignment::Right),
+            task_status_panel_layout[0]
+        );
+        frame.render_widget(chart, task_status_panel_layout[1]);
+    } else {
+        // No task selected → show empty state
+        let list = List::new(vec![ListItem::new("Task list")]).block(
+            Block::default()
+                .title("STATUS")
+                .title(tracking.right_aligned())
+                .borders(Borders::ALL)
+        );
+
+        frame.render_widget(list, task_status_panel_layout[0]);
+
+        let stat_list = List::new(vec![ListItem::new("All task Stats")]).block(
+            Block::default().title("ELAPSED TIME BAR GRAPH").borders(Borders::ALL)
+        );
+
+        frame.render_widget(stat_list, task_status_panel_layout[1]);
+    }
 
-            // FOOTER MENU (Focusable row 3)
-            let footer_cells = vec![
-                "<Esc> Exit",
-                "<UP/DOWN> Move",
-                "<Tab> Focus",
-                "<Enter> Add Task",
-                "<Delete> Delete Task"
-            ];
-            let footer_cells_spans: Vec<Span> = footer_cells
-                .iter()
-                .map(|h| Span::raw(*h))
-                .collect();
-
-            let footer = Row::new(footer_cells_spans).style(
-                Style::new().fg(Color::Rgb(255, 165, 0))
-            );
+    // COMMAND BAR (Focusable row 2)
+    let command_line = if app.prompt.active {
+        Line::from(
+            vec![
+                Span::raw(":"),
+                Span::raw(app.prompt.input.clone()),
+                Span::raw("█").style(Style::new().fg(colors.heading)),
+                // Candidates left over from the last <Tab>
+                Span::raw(
+                    app.status_message
+                        .as_ref()
+                        .map(|hint| format!("   {hint}"))
+                        .unwrap_or_default()
+                ).style(Style::new().fg(colors.muted))
+            ]
+        ).style(Style::new().fg(colors.highlight))
+    } else if let Some(err) = &app.prompt.error {
+        Line::from(format!("Error: {err}")).style(
+            Style::new().fg(colors.alert).add_modifier(Modifier::BOLD)
+        )
+    } else if let Some(msg) = &app.status_message {
+        Line::from(msg.clone()).style(Style::new().fg(colors.success))
+    } else {
+        Line::from(
+            format!(
+                "Command: press {} to type one",
+                app.keymap.key(Action::Command).unwrap_or_default()
+            )
+        ).style(
+            Style::new().fg(colors.label)
+        )
+    };
+
+    frame.render_widget(
+        Paragraph::new(command_line).block(
+            Block::new()
+                .title("COMMAND")
+                .style(Style::new().fg(colors.text))
+                .borders(Borders::ALL)
+                .border_type(ratatui::widgets::BorderType::Plain)
+                .padding(Padding::new(1, 1, 1, 1))
+        ),
+        body[1]
+    );
+
+    search::draw_suggestions(frame, app, areas.slot);
+}
 
-            frame.render_widget(
-                Table::new(Vec::<Row>::new(), [
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(25),
-                    Constraint::Percentage(15),
-                ])
-                    .header(footer)
-                    .style(Style::new().fg(Color::Rgb(0, 200, 180)))
-                    .block(
-                        Block::new()
-                            .title("MENU")
-                            .borders(Borders::ALL)
-                            .border_type(ratatui::widgets::BorderType::Plain)
-                            .padding(Padding::new(1, 1, 1, 1))
-                    ),
-                task_top[4]
-            );
-            if app.show_popup {
-                pop_up(frame);
-            }
-        })?;
-
-        if let Event::Key(key) = event::read()? {
-            // println!("DEBUG: {:?}", key);
-            if key.kind == KeyEventKind::Press {
-                match key.code {
-                    KeyCode::Esc => {
-                        if app.show_popup {
-                            app.show_popup = false; // close popup
-                        } else {
-                            break Ok(()); // quit if no popup open
-                        }
+/// Dispatches a key press in Tasks mode.
+pub fn handle_key(app: &mut App, key: KeyEvent) -> Step {
+    if app.search.active {
+        search::handle_key(app, key);
+    } else if app.prompt.active {
+        match key.code {
+            KeyCode::Esc => app.prompt.close(),
+            KeyCode::Enter => {
+                let line = app.prompt.submit();
+                match command::parse(&line).and_then(|cmd| command::execute(app, cmd)) {
+                    Ok(Outcome::Done(msg)) => {
+                        app.status_message = Some(msg);
+                        app.focus_textarea = false;
                     }
-                    KeyCode::Char('T') => {
-                        return run_timer(terminal, app);
+                    Ok(Outcome::ShowReports) => {
+                        app.status_message = None;
+                        return Step::Switch(Mode::Reports);
                     }
-                    KeyCode::Enter => {
-                        let task_name = app.textarea.lines().join(" "); // take input from textarea
-                        if !task_name.trim().is_empty() {
-                            app.tasks.push(Task {
-                                id: app.next_id,
-                                name: task_name,
-                                status: TaskStatus::Active,
-                                time_spent: Duration::new(0, 0),
-                                started_at: Some(Instant::now()),
-                                expected_duration: Duration::from_secs(7200),
-                            });
-                            app.next_id += 1;
-                            app.textarea = TextArea::default();
-                            app.selected_index = Some(app.tasks.len() - 1);
-                            app.focus_textarea = false;
+                    Err(err) => {
+                        app.prompt.error = Some(err);
+                    }
+                }
+            }
+            KeyCode::Tab => {
+                let candidates = command::complete(app);
+                app.status_message = (!candidates.is_empty()).then(|| candidates.join("  "));
+            }
+            KeyCode::Up => app.prompt.history_prev(),
+            KeyCode::Down => app.prompt.history_next(),
+            KeyCode::Backspace if app.prompt.input.is_empty() => app.prompt.close(),
+            KeyCode::Backspace => {
+                app.prompt.input.pop();
+            }
+            KeyCode::Char(c) => {
+                app.prompt.input.push(c);
+                app.status_message = None;
+            }
+            _ => {}
+        }
+    } else if let Some(id) = app.renaming {
+        match key.code {
+            KeyCode::Esc => {
+                app.renaming = None;
+                app.textarea = TextArea::default();
+            }
+            KeyCode::Enter => {
+                let name = app.textarea.lines().join(" ");
+                if let Some(i) = app.tracker.position(id) {
+                    match app.tracker.rename(i, &name) {
+                        Ok(()) => {
+                            app.status_message = Some(format!("Renamed to '{}'", name.trim()));
+                        }
+                        Err(err) => {
+                            app.prompt.error = Some(err);
                         }
                     }
+                }
+                app.renaming = None;
+                app.textarea = TextArea::default();
+                app.focus_textarea = false;
+            }
+            _ => {
+                app.textarea.input(key);
+            }
+        }
+    } else if !search::handle_suggestion_key(app, key) {
+        // An empty textarea still lets the command and search keys through
+        let action = match app.keymap.action(&key, Mode::Tasks, app.focus_textarea) {
+            None if app.textarea.is_empty() =>
+                app.keymap
+                    .action(&key, Mode::Tasks, false)
+                    .filter(|a| matches!(a, Action::Command | Action::Search)),
+            action => action,
+        };
+        match action {
+            Some(action) => {
+                return perform(app, action);
+            }
+            None => {
+                if app.focus_textarea {
+                    app.textarea.input(key);
+                }
+            }
+        }
+    }
+    Step::Stay
+}
 
-                    KeyCode::Char('P') => {
-                        let task_name = app.textarea.lines().join(" ");
-                        if !task_name.trim().is_empty() {
-                            if
-                                let Some((i, task)) = app.tasks
-                                    .iter_mut()
-                                    .enumerate()
-                                    .find(|(_, t)| t.name == task_name)
-                            {
-                                match task.status {
-                                    TaskStatus::Active => {
-                                        // Pause the task instead of pushing a new one
-                                        task.status = TaskStatus::Paused;
-                                        task.time_spent += task.started_at.unwrap().elapsed();
-                                        task.started_at = None;
-
-                                        // ✅ Start a break timer for THIS task
-                                        app.breaks.insert(i as u32, Break::new());
-                                    }
-                                    TaskStatus::Paused => {
-                                        // Already paused → show popup, don't add
-                                        app.show_popup = true;
-                                    }
-                                }
-                            } else {
-                                // No such task exists → insert as new Paused task
-                                app.tasks.push(Task {
-                                    id: app.next_id,
-                                    name: task_name.clone(),
-                                    status: TaskStatus::Paused,
-                                    time_spent: Duration::new(0, 0),
-                                    started_at: Some(Instant::now()),
-                                    expected_duration: Duration::from_secs(7200),
-                                });
-                                app.next_id += 1;
-                                app.textarea = TextArea::default();
-                                app.selected_index = Some(app.tasks.len() - 1);
-                                app.focus_textarea = false;
-
-                                // ✅ New task paused immediately → add its break state too
-                                app.breaks.insert((app.tasks.len() - 1) as u32, Break::new());
-                            }
-                        }
+/// Runs `action` on the Tasks view, for a key press or a MENU click.
+pub fn perform(app: &mut App, action: Action) -> Step {
+    match action {
+        action @ (
+            Action::Quit |
+            Action::SwitchView |
+            Action::Help |
+            Action::Search |
+            Action::Undo |
+            Action::Redo
+        ) => {
+            return tui::shared(app, action, Mode::Tasks);
+        }
+        Action::Rename => {
+            if let Some(i) = app.selected_index && i < app.tracker.tasks.len() {
+                app.renaming = Some(app.tracker.tasks[i].id);
+                app.textarea = TextArea::new(vec![app.tracker.tasks[i].name.clone()]);
+                app.textarea.move_cursor(tui_textarea::CursorMove::End);
+                app.focus_textarea = true;
+            }
+        }
+        Action::Command => {
+            app.prompt.open();
+            app.status_message = None;
+        }
+        Action::AddTask => {
+            let spec = TaskSpec::parse(&app.textarea.lines().join(" ")); // take input from textarea
+            if !spec.name.is_empty() {
+                match app.tracker.add_task(spec, TaskStatus::Active) {
+                    Ok(i) => {
+                        app.textarea = TextArea::default();
+                        app.selected_index = Some(i);
+                        app.focus_textarea = false;
                     }
+                    Err(err) => {
+                        app.status_message = Some(err);
+                    }
+                }
+            }
+        }
 
-                    KeyCode::Char('R') => {
-                        let task_name = app.textarea.lines().join(" ");
-                        if !task_name.trim().is_empty() {
-                            if let Some(task) = app.tasks.iter_mut().find(|t| t.name == task_name) {
-                                match task.status {
-                                    TaskStatus::Paused => {
-                                        // Active the task instead of pushing a new one
-                                        task.status = TaskStatus::Active;
-
-                                        task.started_at = Some(Instant::now());
-                                    }
-                                    TaskStatus::Active => {
-                                        // Already paused → show popup, don't add
-                                        app.show_popup = true;
-                                    }
+        Action::Pause => {
+            if let Some(spec) = app.typed_or_selected() {
+                if let Some(i) = app.tracker.match_task(&spec) {
+                    match app.tracker.tasks[i].status {
+                        TaskStatus::Active => {
+                            // Pause the task and start its break
+                            match app.tracker.apply_spec(i, &spec) {
+                                Ok(()) => app.tracker.pause(i, None),
+                                Err(err) => {
+                                    app.status_message = Some(err);
                                 }
-                            } else {
-                                // No such task exists → insert as new Paused task
-                                app.tasks.push(Task {
-                                    id: app.next_id,
-                                    name: task_name.clone(),
-                                    status: TaskStatus::Active,
-                                    time_spent: Duration::new(0, 0),
-                                    started_at: Some(Instant::now()),
-                                    expected_duration: Duration::from_secs(7200),
-                                });
-                                app.next_id += 1;
-                                app.textarea = TextArea::default();
-                                app.selected_index = Some(app.tasks.len() - 1);
-                                app.focus_textarea = false;
                             }
                         }
+                        TaskStatus::Paused => {
+                            // Already paused → show popup, don't add
+                            app.show_popup = true;
+                        }
                     }
+                } else {
+                    // No such task exists → insert as new Paused task
+                    match app.tracker.add_task(spec, TaskStatus::Paused) {
+                        Ok(i) => {
+                            app.textarea = TextArea::default();
+                            app.selected_index = Some(i);
+                            app.focus_textarea = false;
 
-                    KeyCode::Delete => {
-                        if let Some(i) = app.selected_index {
-                            if i < app.tasks.len() {
-                                app.tasks.remove(i);
-
-                                if app.tasks.is_empty() {
-                                    app.selected_index = None;
-                                } else if i >= app.tasks.len() {
-                                    app.selected_index = Some(app.tasks.len() - 1);
-                                } else {
-                                    app.selected_index = Some(i);
-                                }
-                            }
+                            // New task paused immediately → its break starts too
+                            app.tracker.pause(i, None);
+                        }
+                        Err(err) => {
+                            app.status_message = Some(err);
                         }
                     }
-                    KeyCode::Tab => {
-                        app.focus_textarea = !app.focus_textarea; // toggle focus
+                }
+            }
+        }
+
+        Action::Resume => {
+            if let Some(spec) = app.typed_or_selected() {
+                let running = app.tracker
+                    .match_task(&spec)
+                    .is_some_and(|i| app.tracker.tasks[i].status == TaskStatus::Active);
+                match app.tracker.start(spec) {
+                    Ok(i) => {
+                        app.textarea = TextArea::default();
+                        app.selected_index = Some(i);
+                        app.focus_textarea = false;
                     }
-                    KeyCode::Down => {
-                        if app.focus_textarea {
-                            app.textarea.input(key);
-                        } else {
-                            if let Some(i) = app.selected_index {
-                                if i + 1 < app.tasks.len() {
-                                    app.selected_index = Some(i + 1);
-                                }
-                            } else if !app.tasks.is_empty() {
-                                app.selected_index = Some(0);
-                            }
-                        }
+                    // Already running → show popup, don't add
+                    Err(_) if running => {
+                        app.show_popup = true;
                     }
-                    KeyCode::Up => {
-                        if app.focus_textarea {
-                            app.textarea.input(key);
-                        } else if let Some(i) = app.selected_index {
-                            if i > 0 {
-                                app.selected_index = Some(i - 1);
-                            }
-                        }
+                    Err(err) => {
+                        app.status_message = Some(err);
                     }
+                }
+            }
+        }
 
-                    _ => {
-                        if app.focus_textarea {
-                            app.textarea.input(key);
-                        }
-                    }
+        Action::Delete => {
+            if let Some(i) = app.selected_index && i < app.tracker.tasks.len() {
+                app.status_message = Some(
+                    format!(
+                        "Moved '{}' to the trash, {} to undo",
+                        app.tracker.tasks[i].name,
+                        app.keymap.key(Action::Undo).unwrap_or_default()
+                    )
+                );
+                app.tracker.delete(i);
+
+                if app.tracker.tasks.is_empty() {
+                    app.selected_index = None;
+                } else if i >= app.tracker.tasks.len() {
+                    app.selected_index = Some(app.tracker.tasks.len() - 1);
+                } else {
+                    app.selected_index = Some(i);
                 }
             }
         }
+        Action::Focus => {
+            app.focus_textarea = !app.focus_textarea; // toggle focus
+        }
+        Action::GroupBy => {
+            app.cycle_group_by();
+        }
+        Action::Filter => {
+            app.cycle_group_filter();
+        }
+        Action::Collapse => {
+            if let Some(i) = app.selected_index && i < app.tracker.tasks.len() {
+                app.toggle_collapsed(i);
+            }
+        }
+        Action::GraphLeaves => {
+            app.graph_leaves = !app.graph_leaves;
+        }
+        Action::NextTask => {
+            app.select_next();
+        }
+        Action::PrevTask => {
+            app.select_prev();
+        }
+        Action::PageUp => {
+            app.select_by(-(app.task_page as isize));
+        }
+        Action::PageDown => {
+            app.select_by(app.task_page as isize);
+        }
+        Action::Top => {
+            app.select_by(isize::MIN);
+        }
+        Action::Bottom => {
+            app.select_by(isize::MAX);
+        }
+        Action::Sort => {
+            app.cycle_sort(&SortBy::TASKS);
+        }
+        _ => {}
     }
+    Step::Stay
 }
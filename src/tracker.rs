@@ -0,0 +1,1410 @@
+//! The tracking core: tasks, the interval log, breaks and the trash, and
+//! every transition between them. Nothing here knows about the terminal;
+//! the CLI and the TUI both drive a `Tracker` and read its `Event`s.
+
+use crate::clock::{Clock, SystemClock};
+use crate::config::{Config, PomodoroConfig, TrackingConfig};
+use crate::pomodoro;
+use crate::util::parse_duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Stable identity of a task. Unlike its index in `tasks` it survives
+/// deletes and reordering, and unlike its number it stays unique when
+/// stores from different machines meet.
+pub type TaskId = Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: TaskId,
+    /// Short handle shown in the ID column and accepted wherever a task is
+    /// named. Handed out in creation order and never reused.
+    pub number: u32,
+    pub name: String,
+    pub status: TaskStatus,
+    /// Start of the stretch currently being tracked, `None` while paused.
+    pub started_at: Option<DateTime<Local>>,
+    pub expected_duration: Duration,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Id of the task this one is a subtask of.
+    #[serde(default)]
+    pub parent: Option<TaskId>,
+}
+
+impl Task {
+    /// Name followed by its `+project` and `@tags`, the way it is typed.
+    pub fn label(&self) -> String {
+        let mut label = self.name.clone();
+        if let Some(project) = &self.project {
+            label.push_str(&format!(" +{project}"));
+        }
+        for tag in &self.tags {
+            label.push_str(&format!(" @{tag}"));
+        }
+        label
+    }
+}
+
+/// One closed stretch of work on a task. The session log is the source of
+/// truth for tracked time; `Tracker::time_spent` is derived from it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interval {
+    pub task_id: TaskId,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub note: Option<String>,
+}
+
+impl Interval {
+    pub fn duration(&self) -> Duration {
+        (self.end - self.start).to_std().unwrap_or_default()
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// `2026-10-17 14:00 → 16:00`, with the end date spelled out only when it
+    /// differs from the start.
+    pub fn span(&self) -> String {
+        let end = if self.end.date_naive() == self.start.date_naive() {
+            self.end.format("%H:%M")
+        } else {
+            self.end.format("%Y-%m-%d %H:%M")
+        };
+        format!("{} → {}", self.start.format("%Y-%m-%d %H:%M"), end)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TaskStatus {
+    Active,
+    Paused,
+}
+
+/// A deleted task kept with everything logged against it, so it can be
+/// restored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trashed {
+    pub task: Task,
+    pub intervals: Vec<Interval>,
+    pub breaks: Option<Break>,
+    pub deleted_at: DateTime<Local>,
+}
+
+/// What the user typed to create or look up a task, e.g.
+/// `Write report +acme @billing ~45m` or `Release 1.2 > Changelog ~30m`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskSpec {
+    pub name: String,
+    pub estimate: Option<Duration>,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+    /// Names of the parents, outermost first.
+    pub parents: Vec<String>,
+}
+
+impl TaskSpec {
+    /// Splits the `~<duration>` estimate, `+project` and `@tags` off the task
+    /// name. A marker word that does not parse stays part of the name. Names
+    /// before a `>` are the parents; markers only count after the last one.
+    pub fn parse(input: &str) -> Self {
+        let mut parents: Vec<String> = input
+            .split('>')
+            .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|p| !p.is_empty())
+            .collect();
+        let input = parents.pop().unwrap_or_default();
+        let mut name = Vec::new();
+        let mut estimate = None;
+        let mut project = None;
+        let mut tags: Vec<String> = Vec::new();
+        for word in input.split_whitespace() {
+            if let Some(d) = word.strip_prefix('~').and_then(parse_duration) {
+                estimate = Some(d);
+            } else if let Some(p) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+                project = Some(p.to_string());
+            } else if let Some(t) = word.strip_prefix('@').filter(|t| !t.is_empty()) {
+                if !tags.iter().any(|existing| existing == t) {
+                    tags.push(t.to_string());
+                }
+            } else {
+                name.push(word);
+            }
+        }
+        Self {
+            name: name.join(" "),
+            estimate,
+            project,
+            tags,
+            parents,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BreakStatus {
+    Started,
+    Ongoing,
+    Completed,
+    Exceeded,
+}
+
+/// Pomodoro state of one task: where it is in the schedule, the break that
+/// is running right now (if any) and how earlier breaks compared with their
+/// plan. Advanced by `pomodoro::tick`, never by the draw code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Break {
+    pub break_status: BreakStatus,
+    /// Position in the cycle, see `pomodoro::phase`.
+    pub phase: usize,
+    pub started_at: Option<DateTime<Local>>,
+    pub planned: Duration,
+    pub elapsed: Duration,
+    pub history: Vec<BreakRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BreakRecord {
+    pub planned: Duration,
+    pub actual: Duration,
+    pub ended_at: DateTime<Local>,
+}
+
+impl BreakRecord {
+    /// How far the break ran past its plan, zero if it ended in time.
+    pub fn overrun(&self) -> Duration {
+        self.actual.saturating_sub(self.planned)
+    }
+}
+
+impl Break {
+    pub fn new() -> Self {
+        Self {
+            break_status: BreakStatus::Completed,
+            phase: 0,
+            started_at: None,
+            planned: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            history: Vec::new(),
+        }
+    }
+
+    /// How far the running break is past its plan.
+    pub fn overrun(&self) -> Duration {
+        self.elapsed.saturating_sub(self.planned)
+    }
+}
+
+/// Something that happened to the tracked data. Collected by the `Tracker`
+/// until whoever drives it calls `take_events`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Added(TaskId),
+    /// The task's clock started, whether it is new or was resumed.
+    Started(TaskId),
+    /// The task's clock stopped and the stretch went into the log.
+    Stopped(TaskId),
+    BreakStarted(TaskId),
+    BreakEnded(TaskId),
+    Renamed(TaskId),
+    Deleted(TaskId),
+    Restored(TaskId),
+    Merged {
+        from: TaskId,
+        into: TaskId,
+    },
+}
+
+/// The tracked data and the rules for changing it. Tasks are addressed by
+/// their index in `tasks`; the intervals and breaks that belong to a task
+/// point at its `id`, so they stay with it when the indices shift.
+#[derive(Debug, Clone)]
+pub struct Tracker {
+    pub tasks: Vec<Task>,
+    pub next_number: u32,
+    pub intervals: Vec<Interval>,
+    pub breaks: HashMap<TaskId, Break>,
+    /// Deleted tasks, oldest first.
+    pub trash: Vec<Trashed>,
+    pub pomodoro: PomodoroConfig,
+    pub tracking: TrackingConfig,
+    pub clock: Arc<dyn Clock>,
+    events: Vec<Event>,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self {
+            tasks: Vec::new(),
+            next_number: 1,
+            intervals: Vec::new(),
+            breaks: HashMap::new(),
+            trash: Vec::new(),
+            pomodoro: PomodoroConfig::default(),
+            tracking: TrackingConfig::default(),
+            clock: Arc::new(SystemClock),
+            events: Vec::new(),
+        }
+    }
+}
+
+impl Tracker {
+    /// Picks up the parts of the config the tracking rules depend on.
+    pub fn configure(&mut self, config: &Config) {
+        self.pomodoro = config.pomodoro.clone();
+        self.tracking = config.tracking.clone();
+    }
+
+    pub fn now(&self) -> DateTime<Local> {
+        self.clock.now()
+    }
+
+    pub fn emit(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Hands over the events since the last call.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Index of the task with this id.
+    pub fn position(&self, id: TaskId) -> Option<usize> {
+        self.tasks.iter().position(|t| t.id == id)
+    }
+
+    /// Finds a task by exact name, falling back to its number.
+    pub fn find(&self, query: &str) -> Option<usize> {
+        self.tasks.iter().position(|t| t.name == query).or_else(|| {
+            let number: u32 = query.parse().ok()?;
+            self.tasks.iter().position(|t| t.number == number)
+        })
+    }
+
+    /// Finds the task a parsed textarea entry refers to.
+    pub fn match_task(&self, spec: &TaskSpec) -> Option<usize> {
+        self.tasks.iter().position(|t| t.name == spec.name)
+    }
+
+    /// Applies any new estimate, project, tags or parent path that came with
+    /// an entry to task `i`. Nothing changes when the path would put a task
+    /// under itself.
+    pub fn apply_spec(&mut self, i: usize, spec: &TaskSpec) -> Result<(), String> {
+        if !spec.parents.is_empty() {
+            self.check_parents(&spec.parents, Some(self.tasks[i].id))?;
+            let parent = self.resolve_parents(&spec.parents)?;
+            let parent = parent.and_then(|id| self.position(id));
+            self.set_parent(i, parent)?;
+        }
+        let task = &mut self.tasks[i];
+        if let Some(estimate) = spec.estimate {
+            task.expected_duration = estimate;
+        }
+        if spec.project.is_some() {
+            task.project = spec.project.clone();
+        }
+        for tag in &spec.tags {
+            if !task.tags.contains(tag) {
+                task.tags.push(tag.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends a new task and returns its index in `tasks`. Parents named in
+    /// the spec that do not exist yet are created paused. Nothing is added
+    /// when the path would put a task under itself.
+    pub fn add_task(&mut self, spec: TaskSpec, status: TaskStatus) -> Result<usize, String> {
+        self.check_parents(&spec.parents, None)?;
+        let parent = self.resolve_parents(&spec.parents)?;
+        let started_at = match status {
+            TaskStatus::Active => Some(self.now()),
+            TaskStatus::Paused => None,
+        };
+        self.tasks.push(Task {
+            id: Uuid::new_v4(),
+            number: self.next_number,
+            name: spec.name,
+            status,
+            started_at,
+            expected_duration: spec.estimate.unwrap_or(Duration::from_secs(
+                self.tracking.default_estimate_minutes * 60,
+            )),
+            project: spec.project,
+            tags: spec.tags,
+            parent,
+        });
+        self.next_number += 1;
+        let i = self.tasks.len() - 1;
+        let id = self.tasks[i].id;
+        self.emit(Event::Added(id));
+        if matches!(self.tasks[i].status, TaskStatus::Active) {
+            self.emit(Event::Started(id));
+            self.pause_others(i);
+        }
+        Ok(i)
+    }
+
+    /// Starts the clock on the task the spec names, resuming it if it exists
+    /// and creating it otherwise. Returns its index in `tasks`.
+    pub fn start(&mut self, spec: TaskSpec) -> Result<usize, String> {
+        let matched = self.match_task(&spec);
+        match matched.or_else(|| self.find(&spec.name)) {
+            Some(i) if matches!(self.tasks[i].status, TaskStatus::Active) => {
+                Err(format!("'{}' is already running", self.tasks[i].name))
+            }
+            Some(i) => {
+                if matched.is_some() {
+                    self.apply_spec(i, &spec)?;
+                }
+                self.resume(i);
+                Ok(i)
+            }
+            None => self.add_task(spec, TaskStatus::Active),
+        }
+    }
+
+    /// Walks a `Release > Changelog` parent path, creating the tasks that are
+    /// missing, and returns the id of the last one. An existing task only
+    /// moves when a name was typed before it, so `Release > Notes` leaves
+    /// wherever `Release` already sits alone.
+    fn resolve_parents(&mut self, names: &[String]) -> Result<Option<TaskId>, String> {
+        let mut parent = None;
+        for name in names {
+            let i = match self.tasks.iter().position(|t| t.name == *name) {
+                Some(i) => {
+                    if let Some(above) = parent.and_then(|id| self.position(id)) {
+                        self.set_parent(i, Some(above))?;
+                    }
+                    i
+                }
+                None => {
+                    let spec = TaskSpec {
+                        name: name.clone(),
+                        estimate: None,
+                        project: None,
+                        tags: Vec::new(),
+                        parents: Vec::new(),
+                    };
+                    let i = self.add_task(spec, TaskStatus::Paused)?;
+                    self.tasks[i].parent = parent;
+                    i
+                }
+            };
+            parent = Some(self.tasks[i].id);
+        }
+        Ok(parent)
+    }
+
+    /// Fails if hanging the `names` path above task `child` (or above a new
+    /// task) would put a task under itself, without changing anything.
+    fn check_parents(&self, names: &[String], child: Option<TaskId>) -> Result<(), String> {
+        let mut parents: HashMap<TaskId, Option<TaskId>> =
+            self.tasks.iter().map(|t| (t.id, t.parent)).collect();
+        // Whether `id` would sit at or below `above`
+        let under = |parents: &HashMap<TaskId, Option<TaskId>>, id, above| {
+            let mut at = Some(above);
+            let mut steps = 0;
+            while let Some(a) = at {
+                // A stored loop counts as one, whatever it is made of
+                if a == id || steps > parents.len() {
+                    return true;
+                }
+                steps += 1;
+                at = parents.get(&a).copied().flatten();
+            }
+            false
+        };
+
+        let mut above: Option<TaskId> = None;
+        let path = names
+            .iter()
+            .map(|n| (n.as_str(), self.tasks.iter().find(|t| t.name == *n)));
+        for (name, task) in path {
+            let id = task.map_or_else(Uuid::new_v4, |t| t.id);
+            if let Some(a) = above {
+                if under(&parents, id, a) {
+                    return Err(format!("'{name}' cannot go under its own subtask"));
+                }
+                parents.insert(id, Some(a));
+            }
+            above = Some(id);
+        }
+        if let (Some(c), Some(a)) = (child, above)
+            && under(&parents, c, a)
+        {
+            let name = self
+                .tasks
+                .iter()
+                .find(|t| t.id == c)
+                .map_or("", |t| &t.name);
+            return Err(format!("'{name}' cannot go under its own subtask"));
+        }
+        Ok(())
+    }
+
+    /// Makes task `i` a subtask of task `parent`, or a top-level task when
+    /// `None`. A task cannot be moved under itself or one of its subtasks.
+    pub fn set_parent(&mut self, i: usize, parent: Option<usize>) -> Result<(), String> {
+        let id = self.tasks[i].id;
+        if let Some(p) = parent
+            && (p == i || self.descendants(id).contains(&p))
+        {
+            return Err(format!(
+                "'{}' cannot go under its own subtask",
+                self.tasks[i].name
+            ));
+        }
+        self.tasks[i].parent = parent.map(|p| self.tasks[p].id);
+        Ok(())
+    }
+
+    /// Indices of the direct subtasks of task `id`.
+    pub fn children(&self, id: TaskId) -> Vec<usize> {
+        (0..self.tasks.len())
+            .filter(|&i| self.tasks[i].parent == Some(id))
+            .collect()
+    }
+
+    /// Indices of every task below task `id`, at any depth.
+    pub fn descendants(&self, id: TaskId) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = self.children(id);
+        while let Some(i) = stack.pop() {
+            if found.contains(&i) {
+                continue;
+            }
+            found.push(i);
+            stack.extend(self.children(self.tasks[i].id));
+        }
+        found
+    }
+
+    /// The task's name preceded by its parents, e.g. `Release > Changelog`.
+    pub fn task_path(&self, task: &Task) -> String {
+        let mut names = vec![task.name.as_str()];
+        let mut parent = task.parent;
+        while let Some(id) = parent {
+            let Some(p) = self.tasks.iter().find(|t| t.id == id) else {
+                break;
+            };
+            if names.len() > self.tasks.len() {
+                break;
+            }
+            names.push(p.name.as_str());
+            parent = p.parent;
+        }
+        names.reverse();
+        names.join(" > ")
+    }
+
+    /// Stops the clock on an active task and logs the stretch it just ran.
+    /// No break starts; see `pause` for that.
+    pub fn stop(&mut self, i: usize, note: Option<String>) {
+        let task = &mut self.tasks[i];
+        task.status = TaskStatus::Paused;
+        if let Some(start) = task.started_at.take() {
+            let id = task.id;
+            self.intervals.push(Interval {
+                task_id: id,
+                start,
+                end: self.now(),
+                note,
+            });
+            self.intervals.sort_by_key(|iv| iv.start);
+            self.emit(Event::Stopped(id));
+        }
+    }
+
+    /// Stops the clock and sends the task on the next break of its
+    /// pomodoro cycle.
+    pub fn pause(&mut self, i: usize, note: Option<String>) {
+        self.stop(i, note);
+        let id = self.tasks[i].id;
+        pomodoro::start_break(self, id);
+    }
+
+    /// Logs past work on a task after the fact. Returns the position of the
+    /// new interval; the log is kept in start order.
+    pub fn add_interval(
+        &mut self,
+        task_id: TaskId,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        note: Option<String>,
+    ) -> Result<usize, String> {
+        if !self.tasks.iter().any(|t| t.id == task_id) {
+            return Err("no such task".to_string());
+        }
+        check_span(start, end, self.now())?;
+        self.intervals.push(Interval {
+            task_id,
+            start,
+            end,
+            note,
+        });
+        Ok(self.resort_intervals(self.intervals.len() - 1))
+    }
+
+    /// Moves the start and/or end of a logged interval.
+    pub fn edit_interval(
+        &mut self,
+        n: usize,
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+    ) -> Result<usize, String> {
+        let iv = self
+            .intervals
+            .get(n)
+            .ok_or(format!("no interval #{}", n + 1))?;
+        let (start, end) = (start.unwrap_or(iv.start), end.unwrap_or(iv.end));
+        check_span(start, end, self.now())?;
+        self.intervals[n].start = start;
+        self.intervals[n].end = end;
+        Ok(self.resort_intervals(n))
+    }
+
+    /// Cuts a logged interval in two at `at`. Both halves keep the note.
+    /// Returns where the second half ended up; the first stays at `n`.
+    pub fn split_interval(&mut self, n: usize, at: DateTime<Local>) -> Result<usize, String> {
+        let iv = self
+            .intervals
+            .get(n)
+            .ok_or(format!("no interval #{}", n + 1))?;
+        if at <= iv.start || at >= iv.end {
+            return Err(format!("{} is not inside #{}", at.format("%H:%M"), n + 1));
+        }
+        let mut second = iv.clone();
+        second.start = at;
+        self.intervals[n].end = at;
+        self.intervals.insert(n + 1, second);
+        Ok(self.resort_intervals(n + 1))
+    }
+
+    pub fn delete_interval(&mut self, n: usize) -> Result<Interval, String> {
+        if n >= self.intervals.len() {
+            return Err(format!("no interval #{}", n + 1));
+        }
+        Ok(self.intervals.remove(n))
+    }
+
+    /// Positions of the logged intervals that overlap interval `n`. Tracked
+    /// time is never clipped, so these are counted twice until fixed.
+    pub fn overlaps_with(&self, n: usize) -> Vec<usize> {
+        let Some(iv) = self.intervals.get(n) else {
+            return Vec::new();
+        };
+        self.intervals
+            .iter()
+            .enumerate()
+            .filter(|&(m, other)| m != n && iv.overlaps(other))
+            .map(|(m, _)| m)
+            .collect()
+    }
+
+    /// Restores start order after interval `n` moved and returns where it
+    /// ended up.
+    fn resort_intervals(&mut self, n: usize) -> usize {
+        let moved = self.intervals.remove(n);
+        let pos = self.intervals.partition_point(|iv| iv.start <= moved.start);
+        self.intervals.insert(pos, moved);
+        pos
+    }
+
+    /// Puts a task back to work, closing the break it was on.
+    pub fn resume(&mut self, i: usize) {
+        let now = self.now();
+        let task = &mut self.tasks[i];
+        task.status = TaskStatus::Active;
+        task.started_at = Some(now);
+        let id = task.id;
+        pomodoro::end_break(self, id);
+        self.emit(Event::Started(id));
+        self.pause_others(i);
+    }
+
+    /// Indices of the tasks whose clock is running.
+    pub fn running(&self) -> Vec<usize> {
+        (0..self.tasks.len())
+            .filter(|&i| matches!(self.tasks[i].status, TaskStatus::Active))
+            .collect()
+    }
+
+    /// Under the single-active policy, pauses everything but task `keep`,
+    /// which was just started. Switching is not a break, so no break starts.
+    fn pause_others(&mut self, keep: usize) {
+        if !self.tracking.single_active {
+            return;
+        }
+        let note = format!("switched to '{}'", self.tasks[keep].name);
+        for i in self.running() {
+            if i != keep {
+                self.stop(i, Some(note.clone()));
+            }
+        }
+    }
+
+    /// Moves a task to the trash together with its logged intervals and
+    /// break state. A running task is paused first so nothing is lost.
+    pub fn delete(&mut self, i: usize) {
+        self.stop(i, None);
+        let task = self.tasks.remove(i);
+        // Subtasks move up a level rather than going with it
+        for t in &mut self.tasks {
+            if t.parent == Some(task.id) {
+                t.parent = task.parent;
+            }
+        }
+        let (intervals, kept) = std::mem::take(&mut self.intervals)
+            .into_iter()
+            .partition(|iv| iv.task_id == task.id);
+        self.intervals = kept;
+        let breaks = self.breaks.remove(&task.id);
+        self.emit(Event::Deleted(task.id));
+        self.trash.push(Trashed {
+            task,
+            intervals,
+            breaks,
+            deleted_at: self.now(),
+        });
+    }
+
+    /// Finds a trashed task by name or number, most recently deleted first.
+    pub fn find_trashed(&self, query: &str) -> Option<usize> {
+        let number: Option<u32> = query.parse().ok();
+        self.trash
+            .iter()
+            .rposition(|t| t.task.name == query || Some(t.task.number) == number)
+    }
+
+    /// Takes a task back out of the trash. Returns its index in `tasks`.
+    pub fn restore(&mut self, n: usize) -> Result<usize, String> {
+        let name = &self
+            .trash
+            .get(n)
+            .ok_or("no such task in the trash")?
+            .task
+            .name;
+        if self.tasks.iter().any(|t| t.name == *name) {
+            return Err(format!("a task named '{name}' already exists"));
+        }
+        let trashed = self.trash.remove(n);
+        if let Some(b) = trashed.breaks {
+            self.breaks.insert(trashed.task.id, b);
+        }
+        self.intervals.extend(trashed.intervals);
+        self.intervals.sort_by_key(|iv| iv.start);
+        let mut task = trashed.task;
+        if task.parent.is_some_and(|p| self.position(p).is_none()) {
+            task.parent = None;
+        }
+        self.emit(Event::Restored(task.id));
+        self.tasks.push(task);
+        Ok(self.tasks.len() - 1)
+    }
+
+    pub fn rename(&mut self, i: usize, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("the name cannot be empty".to_string());
+        }
+        if self
+            .tasks
+            .iter()
+            .enumerate()
+            .any(|(j, t)| j != i && t.name == name)
+        {
+            return Err(format!("a task named '{name}' already exists"));
+        }
+        self.tasks[i].name = name.to_string();
+        self.emit(Event::Renamed(self.tasks[i].id));
+        Ok(())
+    }
+
+    /// Folds task `from` into task `into`: its intervals, break history,
+    /// estimate and tags move over and `from` disappears. Its subtasks move
+    /// under `into`, which may be one of them but nothing deeper, since that
+    /// would leave `into` under a task that is now under it. Returns the new
+    /// index of `into`.
+    pub fn merge(&mut self, from: usize, into: usize) -> Result<usize, String> {
+        if from == into {
+            return Err("cannot merge a task into itself".to_string());
+        }
+        let from_id = self.tasks[from].id;
+        if self.tasks[into].parent != Some(from_id) && self.descendants(from_id).contains(&into) {
+            return Err(format!(
+                "'{}' is nested too deep under '{}' to merge into",
+                self.tasks[into].name, self.tasks[from].name
+            ));
+        }
+        let note = format!("merged into '{}'", self.tasks[into].name);
+        self.stop(from, Some(note));
+        let source = self.tasks.remove(from);
+        let into = if from < into { into - 1 } else { into };
+        let target_id = self.tasks[into].id;
+
+        for iv in &mut self.intervals {
+            if iv.task_id == source.id {
+                iv.task_id = target_id;
+            }
+        }
+        for t in &mut self.tasks {
+            if t.parent == Some(source.id) {
+                t.parent = if t.id == target_id {
+                    source.parent
+                } else {
+                    Some(target_id)
+                };
+            }
+        }
+        if let Some(b) = self.breaks.remove(&source.id) {
+            let target = self.breaks.entry(target_id).or_insert_with(Break::new);
+            target.history.extend(b.history);
+            target.history.sort_by_key(|r| r.ended_at);
+        }
+
+        let target = &mut self.tasks[into];
+        target.expected_duration += source.expected_duration;
+        if target.project.is_none() {
+            target.project = source.project;
+        }
+        for tag in source.tags {
+            if !target.tags.contains(&tag) {
+                target.tags.push(tag);
+            }
+        }
+        self.emit(Event::Merged {
+            from: source.id,
+            into: target_id,
+        });
+        Ok(into)
+    }
+
+    /// How far a task and its subtasks have run past its estimate, `None`
+    /// while within it.
+    pub fn over_budget(&self, task: &Task) -> Option<Duration> {
+        self.rolled_up_time(task)
+            .checked_sub(task.expected_duration)
+            .filter(|over| !over.is_zero())
+    }
+
+    /// Estimated and actual time summed over every task.
+    pub fn estimate_summary(&self) -> (Duration, Duration) {
+        self.tasks
+            .iter()
+            .fold((Duration::ZERO, Duration::ZERO), |(est, act), t| {
+                (est + t.expected_duration, act + self.time_spent(t))
+            })
+    }
+
+    /// Total tracked time: every logged interval plus the running stretch.
+    pub fn time_spent(&self, task: &Task) -> Duration {
+        let logged: Duration = self
+            .intervals
+            .iter()
+            .filter(|iv| iv.task_id == task.id)
+            .map(Interval::duration)
+            .sum();
+        let running = task
+            .started_at
+            .map(|start| (self.now() - start).to_std().unwrap_or_default())
+            .unwrap_or_default();
+        logged + running
+    }
+
+    /// Time spent on a task together with all of its subtasks.
+    pub fn rolled_up_time(&self, task: &Task) -> Duration {
+        self.descendants(task.id)
+            .into_iter()
+            .map(|i| self.time_spent(&self.tasks[i]))
+            .sum::<Duration>()
+            + self.time_spent(task)
+    }
+}
+
+fn check_span(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Result<(), String> {
+    if end <= start {
+        return Err("the end must be after the start".to_string());
+    }
+    if end > now {
+        return Err("cannot log time in the future".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    use chrono::TimeZone;
+
+    fn spec(input: &str) -> TaskSpec {
+        TaskSpec::parse(input)
+    }
+
+    fn hours_ago(h: i64) -> DateTime<Local> {
+        Local::now() - chrono::Duration::hours(h)
+    }
+
+    /// 2 March 2026 at `h:m`.
+    fn at(h: u32, m: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 2, h, m, 0).unwrap()
+    }
+
+    /// A tracker on a clock standing at `h:00` until moved.
+    fn clock_at(h: u32) -> (Tracker, MockClock) {
+        let clock = MockClock::new(at(h, 0));
+        let tracker = Tracker {
+            clock: Arc::new(clock.clone()),
+            ..Tracker::default()
+        };
+        (tracker, clock)
+    }
+
+    fn minutes(m: u64) -> Duration {
+        Duration::from_secs(m * 60)
+    }
+
+    #[test]
+    fn spec_splits_markers_and_parents_off_the_name() {
+        let parsed = spec("Release 1.2 > Changelog +acme @docs ~1h30m @docs");
+        assert_eq!(
+            parsed,
+            TaskSpec {
+                name: "Changelog".to_string(),
+                estimate: Some(Duration::from_secs(90 * 60)),
+                project: Some("acme".to_string()),
+                tags: vec!["docs".to_string()],
+                parents: vec!["Release 1.2".to_string()],
+            }
+        );
+        // Markers that do not parse stay part of the name
+        let parsed = spec("  Fix  ~soon + @  ");
+        assert_eq!(parsed.name, "Fix ~soon + @");
+        assert_eq!((parsed.estimate, parsed.project), (None, None));
+        assert!(parsed.tags.is_empty() && parsed.parents.is_empty());
+    }
+
+    #[test]
+    fn start_creates_a_running_task() {
+        let mut tracker = Tracker::default();
+        let i = tracker.start(spec("Write report +acme ~45m")).unwrap();
+        let task = &tracker.tasks[i];
+        assert_eq!(task.name, "Write report");
+        assert_eq!(task.project.as_deref(), Some("acme"));
+        assert_eq!(task.expected_duration, Duration::from_secs(45 * 60));
+        assert_eq!(task.status, TaskStatus::Active);
+        let id = task.id;
+        assert_eq!(
+            tracker.take_events(),
+            vec![Event::Added(id), Event::Started(id)]
+        );
+    }
+
+    #[test]
+    fn start_refuses_a_running_task() {
+        let mut tracker = Tracker::default();
+        tracker.start(spec("Review")).unwrap();
+        assert!(tracker.start(spec("Review")).is_err());
+        assert_eq!(tracker.tasks.len(), 1);
+    }
+
+    #[test]
+    fn start_resumes_a_paused_task_by_name_or_id() {
+        let mut tracker = Tracker::default();
+        let i = tracker
+            .add_task(spec("Review"), TaskStatus::Paused)
+            .unwrap();
+        assert_eq!(tracker.start(spec("Review")).unwrap(), i);
+        tracker.stop(i, None);
+        assert_eq!(tracker.start(spec("1")).unwrap(), i);
+        assert_eq!(tracker.tasks.len(), 1);
+        assert_eq!(tracker.running(), vec![i]);
+    }
+
+    #[test]
+    fn starting_a_task_stops_the_running_one() {
+        let mut tracker = Tracker::default();
+        let a = tracker.start(spec("A")).unwrap();
+        tracker.take_events();
+        let b = tracker.start(spec("B")).unwrap();
+        assert_eq!(tracker.running(), vec![b]);
+        assert_eq!(tracker.intervals.len(), 1);
+        assert_eq!(
+            tracker.intervals[0].note.as_deref(),
+            Some("switched to 'B'")
+        );
+        assert!(
+            tracker
+                .take_events()
+                .contains(&Event::Stopped(tracker.tasks[a].id))
+        );
+        // Switching is not a break
+        assert!(tracker.breaks.is_empty());
+    }
+
+    #[test]
+    fn parallel_tracking_keeps_both_running() {
+        let mut tracker = Tracker::default();
+        tracker.tracking.single_active = false;
+        tracker.start(spec("A")).unwrap();
+        tracker.start(spec("B")).unwrap();
+        assert_eq!(tracker.running(), vec![0, 1]);
+    }
+
+    #[test]
+    fn resuming_a_task_pauses_the_running_one_without_a_break() {
+        let (mut tracker, clock) = clock_at(9);
+        let a = tracker.start(spec("A")).unwrap();
+        let b = tracker.add_task(spec("B"), TaskStatus::Paused).unwrap();
+        clock.advance(minutes(20));
+        tracker.resume(b);
+
+        assert_eq!(tracker.running(), vec![b]);
+        let iv = &tracker.intervals[0];
+        assert_eq!((iv.start, iv.end), (at(9, 0), at(9, 20)));
+        assert_eq!(iv.note.as_deref(), Some("switched to 'B'"));
+        assert!(!tracker.breaks.contains_key(&tracker.tasks[a].id));
+
+        // Turning the policy off in the config lets both run
+        let mut config = Config::default();
+        config.tracking.single_active = false;
+        tracker.configure(&config);
+        tracker.resume(a);
+        assert_eq!(tracker.running(), vec![a, b]);
+    }
+
+    #[test]
+    fn pause_logs_the_stretch_and_starts_a_break() {
+        let mut tracker = Tracker::default();
+        let i = tracker.start(spec("A")).unwrap();
+        let id = tracker.tasks[i].id;
+        tracker.take_events();
+        tracker.pause(i, Some("lunch".to_string()));
+
+        assert_eq!(tracker.tasks[i].status, TaskStatus::Paused);
+        assert_eq!(tracker.intervals[0].note.as_deref(), Some("lunch"));
+        let b = &tracker.breaks[&id];
+        assert!(b.started_at.is_some());
+        assert_eq!(b.planned, Duration::from_secs(5 * 60));
+        assert_eq!(
+            tracker.take_events(),
+            vec![Event::Stopped(id), Event::BreakStarted(id)]
+        );
+    }
+
+    #[test]
+    fn resume_closes_the_break() {
+        let mut tracker = Tracker::default();
+        let i = tracker.start(spec("A")).unwrap();
+        let id = tracker.tasks[i].id;
+        tracker.pause(i, None);
+        tracker.take_events();
+        tracker.resume(i);
+
+        let b = &tracker.breaks[&id];
+        assert!(b.started_at.is_none());
+        assert_eq!(b.history.len(), 1);
+        assert_eq!(b.break_status, BreakStatus::Completed);
+        // The next break in the cycle is again a short one
+        assert_eq!(pomodoro::next_break(&tracker, id).planned, b.planned);
+        assert_eq!(
+            tracker.take_events(),
+            vec![Event::BreakEnded(id), Event::Started(id)]
+        );
+    }
+
+    #[test]
+    fn time_spent_sums_the_log_and_rolls_up_subtasks() {
+        let mut tracker = Tracker::default();
+        let child = tracker
+            .add_task(spec("Release > Changelog"), TaskStatus::Paused)
+            .unwrap();
+        let parent = tracker.find("Release").unwrap();
+        let (parent_id, child_id) = (tracker.tasks[parent].id, tracker.tasks[child].id);
+        let start = hours_ago(5);
+        let at = |h| start + chrono::Duration::hours(h);
+        tracker.add_interval(parent_id, at(0), at(1), None).unwrap();
+        tracker.add_interval(child_id, at(2), at(4), None).unwrap();
+
+        let hour = Duration::from_secs(3600);
+        assert_eq!(tracker.time_spent(&tracker.tasks[parent]), hour);
+        assert_eq!(tracker.rolled_up_time(&tracker.tasks[parent]), 3 * hour);
+        assert_eq!(
+            tracker.task_path(&tracker.tasks[child]),
+            "Release > Changelog"
+        );
+    }
+
+    #[test]
+    fn time_spent_is_the_logged_stretches_plus_the_running_one() {
+        let (mut tracker, clock) = clock_at(9);
+        let i = tracker.start(spec("Write")).unwrap();
+        clock.advance(minutes(25));
+        tracker.pause(i, None);
+        clock.advance(minutes(5));
+        tracker.resume(i);
+        clock.advance(minutes(10));
+        assert_eq!(tracker.intervals.len(), 1);
+        assert_eq!(tracker.time_spent(&tracker.tasks[i]), minutes(35));
+
+        tracker.stop(i, Some("done".to_string()));
+        let spans: Vec<_> = tracker
+            .intervals
+            .iter()
+            .map(|iv| (iv.start, iv.end, iv.note.as_deref()))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                (at(9, 0), at(9, 25), None),
+                (at(9, 30), at(9, 40), Some("done"))
+            ]
+        );
+        // A paused task's time only changes with its log
+        clock.advance(minutes(60));
+        assert_eq!(tracker.time_spent(&tracker.tasks[i]), minutes(35));
+        tracker.delete_interval(0).unwrap();
+        assert_eq!(tracker.time_spent(&tracker.tasks[i]), minutes(10));
+    }
+
+    #[test]
+    fn starting_a_running_task_changes_nothing() {
+        let mut tracker = Tracker::default();
+        let i = tracker.start(spec("Write ~45m")).unwrap();
+        assert!(tracker.start(spec("Other > Write ~2h +acme @x")).is_err());
+        let task = &tracker.tasks[i];
+        assert_eq!(task.expected_duration, Duration::from_secs(45 * 60));
+        assert_eq!((task.parent, task.project.clone()), (None, None));
+        assert!(task.tags.is_empty());
+        assert_eq!(tracker.tasks.len(), 1);
+    }
+
+    #[test]
+    fn a_path_only_moves_the_tasks_it_names_a_parent_for() {
+        let mut tracker = Tracker::default();
+        tracker.start(spec("X > Release > Changelog")).unwrap();
+        let notes = tracker.start(spec("Release > Notes")).unwrap();
+        let release = tracker.find("Release").unwrap();
+        let x = tracker.find("X").unwrap();
+        assert_eq!(tracker.tasks[release].parent, Some(tracker.tasks[x].id));
+        assert_eq!(
+            tracker.task_path(&tracker.tasks[notes]),
+            "X > Release > Notes"
+        );
+    }
+
+    #[test]
+    fn a_path_that_loops_is_refused_without_changes() {
+        let mut tracker = Tracker::default();
+        tracker
+            .add_task(spec("X > Release > Changelog"), TaskStatus::Paused)
+            .unwrap();
+        let before = tracker.tasks.clone();
+
+        let err = tracker
+            .add_task(spec("Changelog > New > X > Other"), TaskStatus::Paused)
+            .unwrap_err();
+        assert_eq!(err, "'X' cannot go under its own subtask");
+        assert_eq!(tracker.tasks, before);
+
+        let err = tracker.start(spec("Changelog > X ~1h")).unwrap_err();
+        assert_eq!(err, "'X' cannot go under its own subtask");
+        assert_eq!(tracker.tasks, before);
+    }
+
+    #[test]
+    fn add_interval_rejects_bad_spans() {
+        let mut tracker = Tracker::default();
+        let i = tracker.add_task(spec("Write"), TaskStatus::Paused).unwrap();
+        let id = tracker.tasks[i].id;
+        assert!(
+            tracker
+                .add_interval(id, hours_ago(1), hours_ago(2), None)
+                .is_err()
+        );
+        assert!(
+            tracker
+                .add_interval(
+                    id,
+                    hours_ago(1),
+                    Local::now() + chrono::Duration::hours(1),
+                    None
+                )
+                .is_err()
+        );
+        assert!(
+            tracker
+                .add_interval(TaskId::nil(), hours_ago(2), hours_ago(1), None)
+                .is_err()
+        );
+        assert!(tracker.intervals.is_empty());
+    }
+
+    #[test]
+    fn editing_the_log_recomputes_the_total_and_flags_overlaps() {
+        let (mut tracker, _) = clock_at(18);
+        let i = tracker.add_task(spec("Write"), TaskStatus::Paused).unwrap();
+        let id = tracker.tasks[i].id;
+        let total = |tracker: &Tracker| tracker.time_spent(&tracker.tasks[i]);
+
+        assert_eq!(tracker.add_interval(id, at(10, 0), at(11, 0), None), Ok(0));
+        let early = Some("early".to_string());
+        assert_eq!(tracker.add_interval(id, at(8, 0), at(9, 0), early), Ok(0));
+        assert_eq!(total(&tracker), minutes(120));
+        assert!(tracker.overlaps_with(0).is_empty());
+
+        // Stretching the first into the second counts the overlap twice
+        assert_eq!(tracker.edit_interval(0, None, Some(at(10, 30))), Ok(0));
+        assert_eq!(total(&tracker), minutes(210));
+        assert_eq!(tracker.overlaps_with(0), [1]);
+        assert_eq!(tracker.overlaps_with(1), [0]);
+
+        // Moving its start past the other's re-sorts the log
+        assert_eq!(
+            tracker.edit_interval(0, Some(at(11, 0)), Some(at(12, 0))),
+            Ok(1)
+        );
+        assert_eq!(tracker.intervals[1].note.as_deref(), Some("early"));
+        assert!(tracker.overlaps_with(1).is_empty());
+        assert!(tracker.edit_interval(1, Some(at(13, 0)), None).is_err());
+        assert!(tracker.edit_interval(1, None, Some(at(19, 0))).is_err());
+        assert_eq!(tracker.intervals[1].start, at(11, 0));
+
+        assert_eq!(tracker.split_interval(1, at(11, 15)), Ok(2));
+        assert_eq!(total(&tracker), minutes(120));
+        assert!(tracker.split_interval(1, at(12, 0)).is_err());
+        let notes: Vec<_> = tracker
+            .intervals
+            .iter()
+            .map(|iv| iv.note.as_deref())
+            .collect();
+        assert_eq!(notes, [None, Some("early"), Some("early")]);
+
+        assert_eq!(tracker.delete_interval(0).unwrap().start, at(10, 0));
+        assert_eq!(total(&tracker), minutes(60));
+        assert_eq!(
+            tracker.delete_interval(5),
+            Err("no interval #6".to_string())
+        );
+    }
+
+    #[test]
+    fn split_keeps_the_log_in_start_order() {
+        let mut tracker = Tracker::default();
+        let i = tracker.add_task(spec("Write"), TaskStatus::Paused).unwrap();
+        let id = tracker.tasks[i].id;
+        let now = Local::now();
+        let at = |m: i64| now - chrono::Duration::minutes(m);
+        tracker.add_interval(id, at(120), at(0), None).unwrap();
+        tracker.add_interval(id, at(90), at(80), None).unwrap();
+
+        let second = tracker.split_interval(0, at(30)).unwrap();
+        assert_eq!(second, 2);
+        let starts: Vec<_> = tracker.intervals.iter().map(|iv| iv.start).collect();
+        assert_eq!(starts, vec![at(120), at(90), at(30)]);
+        assert_eq!(tracker.intervals[0].end, at(30));
+    }
+
+    #[test]
+    fn delete_trashes_the_task_with_its_log() {
+        let mut tracker = Tracker::default();
+        let child = tracker
+            .add_task(spec("Release > Changelog"), TaskStatus::Paused)
+            .unwrap();
+        let parent = tracker.find("Release").unwrap();
+        let parent_id = tracker.tasks[parent].id;
+        tracker
+            .add_interval(parent_id, hours_ago(2), hours_ago(1), None)
+            .unwrap();
+        let child_id = tracker.tasks[child].id;
+
+        tracker.delete(parent);
+        assert_eq!(tracker.tasks.len(), 1);
+        assert!(tracker.intervals.is_empty());
+        assert_eq!(tracker.trash[0].intervals.len(), 1);
+        // The subtask moved up instead of disappearing
+        assert_eq!(tracker.tasks[0].id, child_id);
+        assert_eq!(tracker.tasks[0].parent, None);
+        assert!(tracker.take_events().contains(&Event::Deleted(parent_id)));
+
+        let n = tracker.find_trashed("Release").unwrap();
+        let i = tracker.restore(n).unwrap();
+        assert_eq!(tracker.tasks[i].id, parent_id);
+        assert_eq!(tracker.intervals.len(), 1);
+        assert!(tracker.trash.is_empty());
+    }
+
+    #[test]
+    fn side_data_stays_with_the_remaining_tasks_after_a_delete() {
+        let mut tracker = Tracker::default();
+        for name in ["A", "B", "C"] {
+            tracker.add_task(spec(name), TaskStatus::Paused).unwrap();
+        }
+        let (b_id, c_id) = (tracker.tasks[1].id, tracker.tasks[2].id);
+        tracker.resume(1);
+        tracker.pause(1, Some("lunch".to_string()));
+        let start = hours_ago(3);
+        let end = start + chrono::Duration::hours(1);
+        tracker
+            .add_interval(c_id, start, end, Some("review".to_string()))
+            .unwrap();
+
+        tracker.delete(0);
+        // B and C moved up a slot; their breaks and notes did not move to
+        // whatever now sits where they were
+        assert_eq!(tracker.tasks[0].id, b_id);
+        assert!(tracker.breaks[&b_id].started_at.is_some());
+        assert!(!tracker.breaks.contains_key(&c_id));
+        let note = |id| {
+            tracker
+                .intervals
+                .iter()
+                .find(|iv| iv.task_id == id)
+                .and_then(|iv| iv.note.as_deref())
+        };
+        assert_eq!(note(b_id), Some("lunch"));
+        assert_eq!(note(c_id), Some("review"));
+        assert_eq!(
+            tracker.time_spent(&tracker.tasks[1]),
+            Duration::from_secs(3600)
+        );
+        // Numbers are not reused, so "3" still means C
+        let c = tracker.add_task(spec("D"), TaskStatus::Paused).unwrap();
+        assert_eq!(tracker.tasks[c].number, 4);
+        assert_eq!(tracker.find("3"), tracker.position(c_id));
+    }
+
+    #[test]
+    fn side_data_follows_the_task_when_the_list_is_reordered() {
+        let mut tracker = Tracker::default();
+        let child = tracker.add_task(spec("A > B"), TaskStatus::Paused).unwrap();
+        let child_id = tracker.tasks[child].id;
+        let parent_id = tracker.tasks[tracker.find("A").unwrap()].id;
+        tracker
+            .add_interval(child_id, hours_ago(2), hours_ago(1), None)
+            .unwrap();
+        tracker.resume(child);
+        tracker.pause(child, None);
+
+        tracker.tasks.reverse();
+        let child = tracker.position(child_id).unwrap();
+        let parent = tracker.position(parent_id).unwrap();
+        assert_ne!(child, parent);
+        assert_eq!(tracker.children(parent_id), vec![child]);
+        assert_eq!(tracker.find("B"), Some(child));
+        assert_eq!(
+            tracker.rolled_up_time(&tracker.tasks[parent]),
+            tracker.time_spent(&tracker.tasks[child])
+        );
+        assert!(tracker.breaks[&child_id].started_at.is_some());
+        assert!(!tracker.breaks.contains_key(&parent_id));
+
+        // Deleting after the reorder still takes the right log with it
+        tracker.delete(child);
+        assert!(tracker.intervals.is_empty());
+        assert_eq!(tracker.trash[0].task.id, child_id);
+        assert!(tracker.trash[0].breaks.is_some());
+    }
+
+    #[test]
+    fn delete_stops_a_running_task_first() {
+        let mut tracker = Tracker::default();
+        let i = tracker.start(spec("A")).unwrap();
+        tracker.delete(i);
+        assert!(tracker.running().is_empty());
+        assert_eq!(tracker.trash[0].intervals.len(), 1);
+        assert_eq!(tracker.trash[0].task.started_at, None);
+    }
+
+    #[test]
+    fn restore_refuses_a_taken_name() {
+        let mut tracker = Tracker::default();
+        tracker.add_task(spec("A"), TaskStatus::Paused).unwrap();
+        tracker.delete(0);
+        tracker.add_task(spec("A"), TaskStatus::Paused).unwrap();
+        assert!(tracker.restore(0).is_err());
+        assert_eq!(tracker.trash.len(), 1);
+    }
+
+    #[test]
+    fn merge_moves_the_log_over() {
+        let mut tracker = Tracker::default();
+        let a = tracker.add_task(spec("A ~1h"), TaskStatus::Paused).unwrap();
+        let b = tracker
+            .add_task(spec("B ~30m @misc"), TaskStatus::Paused)
+            .unwrap();
+        let (a_id, b_id) = (tracker.tasks[a].id, tracker.tasks[b].id);
+        tracker
+            .add_interval(a_id, hours_ago(2), hours_ago(1), None)
+            .unwrap();
+
+        let into = tracker.merge(a, b).unwrap();
+        assert_eq!(tracker.tasks.len(), 1);
+        assert_eq!(tracker.tasks[into].id, b_id);
+        assert_eq!(tracker.intervals[0].task_id, b_id);
+        assert_eq!(
+            tracker.tasks[into].expected_duration,
+            Duration::from_secs(90 * 60)
+        );
+        assert!(tracker.merge(into, into).is_err());
+    }
+
+    #[test]
+    fn merge_into_a_nested_subtask_is_refused() {
+        let mut tracker = Tracker::default();
+        let g = tracker
+            .add_task(spec("S > C > G"), TaskStatus::Paused)
+            .unwrap();
+        let s = tracker.find("S").unwrap();
+        let c = tracker.find("C").unwrap();
+        let (s_id, c_id) = (tracker.tasks[s].id, tracker.tasks[c].id);
+
+        assert!(tracker.merge(s, g).is_err());
+        assert_eq!(tracker.tasks.len(), 3);
+        assert_eq!(tracker.tasks[g].parent, Some(c_id));
+        assert_eq!(tracker.tasks[c].parent, Some(s_id));
+
+        // A direct subtask takes the merged task's place instead
+        let into = tracker.merge(s, c).unwrap();
+        assert_eq!(tracker.tasks[into].parent, None);
+        assert_eq!(tracker.rolled_up_time(&tracker.tasks[into]), Duration::ZERO);
+    }
+
+    #[test]
+    fn rename_keeps_names_unique() {
+        let mut tracker = Tracker::default();
+        tracker.add_task(spec("A"), TaskStatus::Paused).unwrap();
+        tracker.add_task(spec("B"), TaskStatus::Paused).unwrap();
+        assert!(tracker.rename(1, "A").is_err());
+        assert!(tracker.rename(1, "  ").is_err());
+        tracker.rename(1, " C ").unwrap();
+        assert_eq!(tracker.tasks[1].name, "C");
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let mut tracker = Tracker::default();
+        let child = tracker.add_task(spec("A > B"), TaskStatus::Paused).unwrap();
+        let parent = tracker.find("A").unwrap();
+        assert!(tracker.set_parent(parent, Some(child)).is_err());
+        assert!(tracker.set_parent(parent, Some(parent)).is_err());
+        tracker.set_parent(child, None).unwrap();
+        assert_eq!(tracker.tasks[child].parent, None);
+    }
+}
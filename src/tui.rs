@@ -0,0 +1,577 @@
+//! The TUI main loop. One loop runs for the whole session: it ticks the
+//! timers, draws the chrome every mode shares (header, MODES row and MENU
+//! footer) around the body of the current mode, and hands key presses to
+//! that mode's dispatcher. A mode is a `draw`, a `handle_key` and a `perform`
+//! function; adding one means a `Mode` variant and a line in each match
+//! below. Clicks go through `mouse`, which runs footer entries via `perform`.
+
+use crate::help_ui;
+use crate::history::{self, History};
+use crate::idle;
+use crate::idle_ui;
+use crate::keymap::{self, Action};
+use crate::mouse::{self, Target};
+use crate::pomodoro;
+use crate::redundancy_warning::pop_up;
+use crate::report_ui;
+use crate::search::Search;
+use crate::settings_ui;
+use crate::task_ui;
+use crate::timer;
+use crate::tracker;
+use crate::util::App;
+
+use crossterm::event::{self, Event, KeyEventKind, MouseEventKind};
+use ratatui::prelude::*;
+use ratatui::text::Span;
+use ratatui::widgets::{Block, BorderType, Borders, Cell, Padding, Row, Table};
+
+use std::io;
+use std::time::Duration;
+
+/// How often a session with a task or break running is saved even when
+/// nothing is pressed, so a crash loses at most this much of it.
+const SAVE_EVERY: chrono::TimeDelta = chrono::TimeDelta::minutes(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Tasks,
+    Timer,
+    Reports,
+    Settings,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Tasks, Mode::Timer, Mode::Reports, Mode::Settings];
+
+    /// The mode the switch-view key goes to.
+    pub fn next(self) -> Mode {
+        match self {
+            Mode::Tasks => Mode::Timer,
+            Mode::Timer => Mode::Reports,
+            Mode::Reports => Mode::Settings,
+            Mode::Settings => Mode::Tasks,
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Mode::Tasks => "Tasks",
+            Mode::Timer => "Timer",
+            Mode::Reports => "Reports",
+            Mode::Settings => "Settings",
+        }
+    }
+
+    /// The actions the mode handles. A key may mean different things in
+    /// different modes, but only one thing within a mode.
+    pub fn actions(self) -> &'static [Action] {
+        use Action::*;
+        match self {
+            Mode::Tasks => &[
+                Quit,
+                SwitchView,
+                Help,
+                Command,
+                Search,
+                Undo,
+                Redo,
+                AddTask,
+                Pause,
+                Resume,
+                Delete,
+                Rename,
+                NextTask,
+                PrevTask,
+                PageUp,
+                PageDown,
+                Top,
+                Bottom,
+                Sort,
+                Focus,
+                GroupBy,
+                Filter,
+                Collapse,
+                GraphLeaves,
+            ],
+            Mode::Timer => &[
+                Quit,
+                SwitchView,
+                Help,
+                Search,
+                Undo,
+                Redo,
+                Resume,
+                Delete,
+                NextTask,
+                PrevTask,
+                PageUp,
+                PageDown,
+                Top,
+                Bottom,
+                LogsPageUp,
+                LogsPageDown,
+                LogsTop,
+                LogsBottom,
+                Sort,
+                Focus,
+            ],
+            Mode::Reports => &[
+                Quit,
+                SwitchView,
+                Help,
+                DayReport,
+                WeekReport,
+                MonthReport,
+                Breakdown,
+                EarlierRange,
+                LaterRange,
+                ExportCsv,
+                ExportJson,
+            ],
+            Mode::Settings => &[
+                Quit,
+                SwitchView,
+                Help,
+                EditSetting,
+                NextTask,
+                PrevTask,
+                PageUp,
+                PageDown,
+                Top,
+                Bottom,
+            ],
+        }
+    }
+
+    /// The actions the MENU footer lists; `?` shows the rest.
+    pub fn footer(self) -> &'static [Action] {
+        use Action::*;
+        match self {
+            Mode::Tasks => &[
+                Quit, SwitchView, AddTask, Pause, Resume, Delete, Rename, Focus, Command, Help,
+            ],
+            Mode::Timer => &[Quit, SwitchView, Resume, Delete, Focus, Help],
+            Mode::Reports => &[
+                Quit,
+                SwitchView,
+                DayReport,
+                WeekReport,
+                MonthReport,
+                Breakdown,
+                ExportCsv,
+                Help,
+            ],
+            Mode::Settings => &[Quit, SwitchView, EditSetting, Help],
+        }
+    }
+}
+
+/// What a mode's dispatcher wants the loop to do after a key press.
+pub enum Step {
+    Stay,
+    Switch(Mode),
+    Quit,
+}
+
+/// The parts of the screen the chrome leaves to the mode.
+#[derive(Clone, Copy)]
+pub struct Areas {
+    /// The box left of the MODES row, e.g. the textarea.
+    pub slot: Rect,
+    pub body: Rect,
+}
+
+/// Where the loop gets its input: the terminal when running for real, a
+/// script in tests.
+pub trait EventSource {
+    /// Waits up to `timeout` for the next event; `None` when none came.
+    fn next(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+}
+
+/// Reads events from the terminal through crossterm.
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if event::poll(timeout)? {
+            return event::read().map(Some);
+        }
+        Ok(None)
+    }
+}
+
+/// Runs the TUI until the quit key, saving as it goes, and hands the app
+/// back.
+pub fn run<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mut app: App,
+    events: &mut impl EventSource,
+) -> Result<App, Box<dyn std::error::Error>> {
+    let mut mode = Mode::Tasks;
+    loop {
+        history::commit(&mut app);
+        if let Err(err) = autosave(&mut app, false) {
+            app.status_message = Some(err);
+        }
+        for event in app.tracker.take_events() {
+            if let tracker::Event::Started(id) = event {
+                app.green_log_task = Some(id);
+            }
+        }
+        pomodoro::tick(&mut app.tracker);
+        idle::tick(&mut app);
+        app.hits.clear();
+        terminal.draw(|frame| {
+            let areas = chrome(frame, &mut app, mode);
+            match mode {
+                Mode::Tasks => task_ui::draw(frame, &mut app, areas),
+                Mode::Timer => timer::draw(frame, &mut app, areas),
+                Mode::Reports => report_ui::draw(frame, &app, areas),
+                Mode::Settings => settings_ui::draw(frame, &mut app, areas),
+            }
+            if app.show_popup {
+                pop_up(frame, app.config.colors.alert);
+            }
+            help_ui::pop_up(frame, &app, mode);
+            idle_ui::pop_up(frame, &app);
+        })?;
+
+        // Wake up at least once per tick so running timers redraw without input
+        let step = match events.next(app.tick_rate)? {
+            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                idle::touch(&mut app);
+                // Typing only edits text, the popup's keys decide though
+                if app.away.is_some() || !(app.typing() && keymap::is_text_key(&key)) {
+                    history::begin(&mut app);
+                }
+                if idle_ui::handle_key(&mut app, key) {
+                    continue;
+                }
+                if app.show_help {
+                    app.show_help = false;
+                    continue;
+                }
+                match mode {
+                    Mode::Tasks => task_ui::handle_key(&mut app, key),
+                    Mode::Timer => timer::handle_key(&mut app, key),
+                    Mode::Reports => report_ui::handle_key(&mut app, key),
+                    Mode::Settings => settings_ui::handle_key(&mut app, key),
+                }
+            }
+            // Popups only answer to keys, and moving the pointer is no input
+            Some(Event::Mouse(mouse))
+                if matches!(
+                    mouse.kind,
+                    MouseEventKind::Down(_) | MouseEventKind::ScrollDown | MouseEventKind::ScrollUp
+                ) && app.away.is_none()
+                    && !app.show_help =>
+            {
+                idle::touch(&mut app);
+                history::begin(&mut app);
+                mouse::handle(&mut app, mouse, mode)
+            }
+            _ => Step::Stay,
+        };
+        match step {
+            Step::Stay => {}
+            Step::Switch(next) => mode = next,
+            Step::Quit => {
+                history::commit(&mut app);
+                autosave(&mut app, true)?;
+                return Ok(app);
+            }
+        }
+    }
+}
+
+/// Writes the tracker after every change and every `SAVE_EVERY` while
+/// something runs, or for good when `closing`. When another `chronos`
+/// wrote the file in the meantime, its version is loaded instead; if this
+/// session has changes of its own too, nothing is written over it.
+fn autosave(app: &mut App, closing: bool) -> Result<(), String> {
+    let Some(store) = &mut app.autosave else {
+        return Ok(());
+    };
+    let version = app.history.version();
+    let unsaved = store.version != version;
+    if store.changed_on_disk() {
+        if unsaved {
+            // Refuses, saying why
+            return store.save(&app.tracker).map_err(|err| err.to_string());
+        }
+        if closing {
+            return Ok(());
+        }
+        let mut tracker = store.load().map_err(|err| err.to_string())?;
+        tracker.clock = app.tracker.clock.clone();
+        tracker.configure(&app.config);
+        app.tracker = tracker;
+        // Undoing past the reload would bring back what the other session replaced
+        app.history = History::default();
+        store.version = app.history.version();
+        app.selected_index = match app.tracker.tasks.len() {
+            0 => None,
+            len => Some(app.selected_index.unwrap_or(0).min(len - 1)),
+        };
+        app.status_message = Some("Loaded the changes saved from outside".to_string());
+        return Ok(());
+    }
+
+    let now = app.tracker.now();
+    let running = !app.tracker.running().is_empty()
+        || app.tracker.breaks.values().any(|b| b.started_at.is_some());
+    let due = store.saved_at.is_none_or(|at| now - at >= SAVE_EVERY);
+    if unsaved || closing || (running && due) {
+        store.save(&app.tracker).map_err(|err| err.to_string())?;
+        store.version = version;
+        store.saved_at = Some(now);
+    }
+    Ok(())
+}
+
+/// Runs `action` in `mode` as if its key had been pressed.
+pub fn perform(app: &mut App, action: Action, mode: Mode) -> Step {
+    match mode {
+        Mode::Tasks => task_ui::perform(app, action),
+        Mode::Timer => timer::perform(app, action),
+        Mode::Reports => report_ui::perform(app, action),
+        Mode::Settings => settings_ui::perform(app, action),
+    }
+}
+
+/// Handles the actions that mean the same in every mode.
+pub fn shared(app: &mut App, action: Action, mode: Mode) -> Step {
+    match action {
+        Action::Quit if app.show_popup => app.show_popup = false,
+        // Esc drops a `/` filter before it quits
+        Action::Quit if !app.search.query.is_empty() => app.search = Search::default(),
+        Action::Quit => return Step::Quit,
+        Action::SwitchView => return Step::Switch(mode.next()),
+        Action::Help => app.show_help = true,
+        Action::Search => {
+            app.search.active = true;
+            app.focus_textarea = false;
+        }
+        Action::Undo => {
+            app.status_message = Some(
+                if history::undo(app) {
+                    "Undid the last change"
+                } else {
+                    "Nothing to undo"
+                }
+                .to_string(),
+            );
+        }
+        Action::Redo => {
+            app.status_message = Some(
+                if history::redo(app) {
+                    "Redid the last undone change"
+                } else {
+                    "Nothing to redo"
+                }
+                .to_string(),
+            );
+        }
+        _ => {}
+    }
+    Step::Stay
+}
+
+/// Draws the background, header, MODES row and MENU footer, registering
+/// the tabs and footer entries for the mouse.
+fn chrome(frame: &mut Frame, app: &mut App, mode: Mode) -> Areas {
+    let colors = app.config.colors;
+    frame.render_widget(
+        Block::default().style(Style::default().bg(colors.background)),
+        frame.area(),
+    );
+
+    let layout = &app.config.layout;
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Percentage(layout.header), // Header
+            Constraint::Percentage(layout.nav),    // Slot and nav tabs
+            Constraint::Percentage(layout.body),   // The mode's body
+            Constraint::Percentage(layout.footer), // Footer
+        ])
+        .split(frame.area());
+
+    // HEADER
+    let welcome = match app.simulate {
+        Some(speed) => format!(
+            "[Simulated {speed}x, {}]",
+            app.tracker.now().format("%Y-%m-%d %H:%M:%S")
+        ),
+        None => "[Welcome to Chronos]".to_string(),
+    };
+    let header = Row::new(vec![
+        Cell::from(welcome),
+        Cell::from(format!("[Mode: {}]", mode.title())),
+    ])
+    .style(Style::new().fg(colors.label));
+
+    frame.render_widget(
+        Table::new(
+            Vec::<Row>::new(),
+            [Constraint::Percentage(90), Constraint::Percentage(10)],
+        )
+        .header(header)
+        .style(Style::new().fg(colors.text))
+        .block(
+            Block::new()
+                .title(Span::styled(
+                    "Chronos",
+                    Style::new().fg(colors.heading).add_modifier(Modifier::BOLD),
+                ))
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain)
+                .padding(Padding::new(1, 1, 1, 1)),
+        ),
+        rows[0],
+    );
+
+    // NAVIGATION TABS
+    let nav_row = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(20), Constraint::Percentage(80)])
+        .split(rows[1]);
+
+    let mut nav_cells = Vec::new();
+    let mut nav_widths = Vec::new();
+    for (n, m) in Mode::ALL.into_iter().enumerate() {
+        if n > 0 {
+            nav_cells.push(Cell::from("|"));
+            nav_widths.push(Constraint::Length(1));
+        }
+        let title = format!("[{}]", m.title());
+        nav_widths.push(Constraint::Length(title.chars().count() as u16));
+        let cell = Cell::from(title);
+        nav_cells.push(if m == mode {
+            cell.style(Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED))
+        } else {
+            cell
+        });
+    }
+
+    let nav_block = Block::new()
+        .title("MODES")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .padding(Padding::new(1, 1, 1, 1));
+    // Every other cell is a `|` separator
+    let nav_cell_areas = mouse::cells(nav_block.inner(nav_row[1]), &nav_widths);
+    for (m, area) in Mode::ALL.into_iter().zip(nav_cell_areas.iter().step_by(2)) {
+        app.hits.add(area.intersection(nav_row[1]), Target::Mode(m));
+    }
+
+    frame.render_widget(
+        Table::new(Vec::<Row>::new(), nav_widths)
+            .header(Row::new(nav_cells).style(Style::new().fg(colors.heading)))
+            .style(Style::new().fg(colors.text))
+            .block(nav_block),
+        nav_row[1],
+    );
+
+    // FOOTER MENU, generated from the keymap
+    let footer_entries = app.keymap.footer(mode);
+    let widths: Vec<Constraint> = footer_entries
+        .iter()
+        .map(|(_, h)| Constraint::Min(h.chars().count() as u16))
+        .collect();
+    let footer_block = Block::new()
+        .title("MENU")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .padding(Padding::new(1, 1, 1, 1));
+    let footer_cell_areas = mouse::cells(footer_block.inner(rows[3]), &widths);
+    for ((action, _), area) in footer_entries.iter().zip(footer_cell_areas.iter()) {
+        app.hits
+            .add(area.intersection(rows[3]), Target::Action(*action));
+    }
+    let footer = Row::new(footer_entries.into_iter().map(|(_, h)| Cell::from(h)))
+        .style(Style::new().fg(colors.heading));
+
+    frame.render_widget(
+        Table::new(Vec::<Row>::new(), widths)
+            .header(footer)
+            .style(Style::new().fg(colors.text))
+            .block(footer_block),
+        rows[3],
+    );
+
+    Areas {
+        slot: nav_row[0],
+        body: rows[2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::Tracker;
+
+    #[test]
+    fn the_switch_key_visits_every_mode_and_comes_back() {
+        let mut mode = Mode::Tasks;
+        let mut seen = Vec::new();
+        for _ in Mode::ALL {
+            seen.push(mode);
+            mode = mode.next();
+        }
+        assert_eq!(seen, Mode::ALL);
+        assert_eq!(mode, Mode::Tasks);
+    }
+
+    #[test]
+    fn every_footer_entry_is_an_action_of_its_mode() {
+        for mode in Mode::ALL {
+            for action in mode.footer() {
+                assert!(mode.actions().contains(action), "{action:?} in {mode:?}");
+            }
+            for action in [Action::Quit, Action::SwitchView, Action::Help] {
+                assert!(mode.actions().contains(&action), "{action:?} in {mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn quit_closes_what_is_open_before_it_quits() {
+        let mut app = App::new(Tracker::default());
+        app.show_popup = true;
+        app.search.query = "plan".to_string();
+
+        assert!(matches!(
+            perform(&mut app, Action::Quit, Mode::Timer),
+            Step::Stay
+        ));
+        assert!(!app.show_popup);
+        assert!(matches!(
+            perform(&mut app, Action::Quit, Mode::Timer),
+            Step::Stay
+        ));
+        assert!(app.search.query.is_empty());
+        assert!(matches!(
+            perform(&mut app, Action::Quit, Mode::Timer),
+            Step::Quit
+        ));
+    }
+
+    #[test]
+    fn each_mode_switches_to_the_next() {
+        let mut app = App::new(Tracker::default());
+        for mode in Mode::ALL {
+            match perform(&mut app, Action::SwitchView, mode) {
+                Step::Switch(next) => assert_eq!(next, mode.next()),
+                _ => panic!("{mode:?} did not switch"),
+            }
+        }
+        assert!(matches!(
+            perform(&mut app, Action::Help, Mode::Reports),
+            Step::Stay
+        ));
+        assert!(app.show_help);
+    }
+}
@@ -0,0 +1,765 @@
+//! Replays scripted key presses through the real TUI loop on a
+//! `TestBackend`, with a mock clock standing in for the wall clock, and
+//! checks what ends up on screen.
+
+use crate::banner::tui_banner;
+use crate::clock::MockClock;
+use crate::config::KeysConfig;
+use crate::keymap::{Keymap, Preset};
+use crate::storage::{self, Autosave};
+use crate::tracker::{Break, BreakRecord, TaskSpec, TaskStatus, Tracker};
+use crate::tui::{self, EventSource};
+use crate::util::App;
+
+use chrono::{Local, TimeZone};
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::{Terminal, backend::TestBackend};
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+enum Input {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    /// One tick without input, after which the clock has moved on.
+    Wait(Duration),
+}
+
+/// A scripted event source. Running out of input is an error, so a script
+/// that forgets to quit fails instead of hanging.
+struct Script {
+    clock: MockClock,
+    inputs: VecDeque<Input>,
+    /// How long the loop was ready to wait each time it asked for input.
+    timeouts: Vec<Duration>,
+}
+
+impl Script {
+    fn new(clock: &MockClock) -> Self {
+        Self {
+            clock: clock.clone(),
+            inputs: VecDeque::new(),
+            timeouts: Vec::new(),
+        }
+    }
+
+    fn key(self, code: KeyCode) -> Self {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    fn key_with(mut self, code: KeyCode, modifiers: KeyModifiers) -> Self {
+        self.inputs
+            .push_back(Input::Key(KeyEvent::new(code, modifiers)));
+        self
+    }
+
+    fn mouse(mut self, kind: MouseEventKind, (column, row): (u16, u16)) -> Self {
+        self.inputs.push_back(Input::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }));
+        self
+    }
+
+    fn click(self, at: (u16, u16)) -> Self {
+        self.mouse(MouseEventKind::Down(MouseButton::Left), at)
+    }
+
+    fn text(mut self, text: &str) -> Self {
+        for c in text.chars() {
+            self = self.key(KeyCode::Char(c));
+        }
+        self
+    }
+
+    /// Lets `minutes` pass in short ticks, the way the real loop sees time
+    /// go by, so the idle check does not mistake it for a suspend.
+    fn wait(mut self, minutes: u64) -> Self {
+        for _ in 0..minutes * 6 {
+            self.inputs.push_back(Input::Wait(Duration::from_secs(10)));
+        }
+        self
+    }
+}
+
+impl EventSource for Script {
+    fn next(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        self.timeouts.push(timeout);
+        match self.inputs.pop_front() {
+            Some(Input::Key(key)) => Ok(Some(Event::Key(key))),
+            Some(Input::Mouse(mouse)) => Ok(Some(Event::Mouse(mouse))),
+            Some(Input::Wait(by)) => {
+                self.clock.advance(by);
+                Ok(None)
+            }
+            None => Err(io::Error::other("the script ran out before quitting")),
+        }
+    }
+}
+
+fn setup() -> (App, MockClock) {
+    let clock = MockClock::new(Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap());
+    let mut tracker = Tracker::default();
+    tracker.clock = Arc::new(clock.clone());
+    (App::new(tracker), clock)
+}
+
+/// Runs the script and returns the app as it quit plus the last frame.
+fn replay(app: App, mut script: Script) -> (App, String) {
+    let mut terminal = Terminal::new(TestBackend::new(240, 60)).unwrap();
+    let app = tui::run(&mut terminal, app, &mut script).unwrap();
+    (app, screen(&terminal))
+}
+
+/// Runs a script that stops short of quitting, e.g. halfway through typing,
+/// and returns the last frame.
+fn snapshot(app: App, mut script: Script) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(240, 60)).unwrap();
+    assert!(tui::run(&mut terminal, app, &mut script).is_err());
+    screen(&terminal)
+}
+
+fn screen(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    let mut lines = Vec::new();
+    for y in 0..buffer.area.height {
+        let line: String = (0..buffer.area.width)
+            .map(|x| buffer[(x, y)].symbol())
+            .collect();
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+/// Column and row of the first place `text` shows up on `screen`.
+fn find(screen: &str, text: &str) -> (u16, u16) {
+    screen
+        .lines()
+        .enumerate()
+        .find_map(|(y, line)| {
+            let x = line.find(text)?;
+            Some((line[..x].chars().count() as u16, y as u16))
+        })
+        .unwrap_or_else(|| panic!("'{text}' is not on screen"))
+}
+
+#[test]
+fn adding_a_task_lists_it_as_active() {
+    let (app, clock) = setup();
+    let script = Script::new(&clock)
+        .text("Write report +acme ~45m")
+        .key(KeyCode::Enter)
+        .wait(1)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+
+    assert_eq!(app.tracker.tasks.len(), 1);
+    assert_eq!(app.tracker.tasks[0].status, TaskStatus::Active);
+    let row = screen
+        .lines()
+        .find(|l| l.contains("Write report +acme"))
+        .expect("the task is listed");
+    assert!(row.contains("Active"), "{row}");
+    assert!(row.contains("00:01:00"), "{row}");
+    assert!(row.contains("00:45:00"), "{row}");
+}
+
+#[test]
+fn pausing_starts_a_break_in_the_timer_logs() {
+    let (app, clock) = setup();
+    let script = Script::new(&clock)
+        .text("Focus")
+        .key(KeyCode::Enter)
+        .wait(3)
+        .key(KeyCode::Char('P'))
+        .wait(4)
+        .key(KeyCode::Char('T'))
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+
+    assert!(screen.contains("[Mode: Timer]"));
+    assert!(screen.contains(
+        "Break_duration: 00:04:00 / 00:05:00 task_name: Focus short_break_status: Ongoing"
+    ));
+    assert!(!screen.contains("Break time exceeded"));
+    assert_eq!(app.tracker.intervals.len(), 1);
+    assert_eq!(
+        app.tracker.intervals[0].duration(),
+        Duration::from_secs(3 * 60)
+    );
+}
+
+#[test]
+fn the_loop_redraws_every_tick_without_input() {
+    let (mut app, clock) = with_tasks(&["Alpha"]);
+    app.tick_rate = Duration::from_millis(250);
+    let mut script = Script::new(&clock).key(KeyCode::Char('R')).wait(2);
+    let mut terminal = Terminal::new(TestBackend::new(240, 60)).unwrap();
+    assert!(tui::run(&mut terminal, app, &mut script).is_err());
+
+    // The key, twelve ticks and the frame drawn after the last one
+    assert_eq!(script.timeouts, vec![Duration::from_millis(250); 14]);
+    assert!(screen(&terminal).contains("● Tracking Alpha 00:02:00"));
+}
+
+#[test]
+fn switching_tasks_moves_the_tracking_indicator() {
+    let (app, clock) = with_tasks(&["Alpha", "Bravo"]);
+    let script = Script::new(&clock)
+        .key(KeyCode::Char('R'))
+        .wait(1)
+        .key(KeyCode::Down)
+        .key(KeyCode::Char('R'))
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+
+    assert_eq!(app.tracker.running(), vec![1]);
+    assert!(screen.contains("● Tracking Bravo 00:00:00"));
+    assert!(!screen.contains("● Tracking Alpha"));
+}
+
+#[test]
+fn an_overlong_break_is_flagged() {
+    let (app, clock) = setup();
+    let script = Script::new(&clock)
+        .text("Focus")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Char('P'))
+        .key(KeyCode::Char('T'))
+        .wait(7)
+        .key(KeyCode::Esc);
+    let (_, screen) = replay(app, script);
+
+    assert!(screen.contains("short_break_status: Exceeded"));
+    assert!(screen.contains("Break time exceeded for task: Focus by 00:02:00"));
+}
+
+#[test]
+fn resuming_records_the_break() {
+    let (app, clock) = setup();
+    let script = Script::new(&clock)
+        .text("Focus")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Char('P'))
+        .wait(5)
+        .key(KeyCode::Char('R'))
+        .key(KeyCode::Char('T'))
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+
+    assert!(screen.contains(
+        "Break 1 task_name: Focus planned: 00:05:00 actual: 00:05:00 break_status: Completed"
+    ));
+    assert!(screen.contains("Task: Focus is activated successfully"));
+    assert_eq!(app.tracker.running(), vec![0]);
+}
+
+#[test]
+fn the_end_of_a_work_phase_asks_for_a_break() {
+    let (mut app, clock) = setup();
+    // Nobody touches the keyboard for 25 minutes, which is fine here
+    app.config.idle.enabled = false;
+    let script = Script::new(&clock)
+        .text("Focus")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Char('T'))
+        .wait(25)
+        .key(KeyCode::Esc);
+    let (_, screen) = replay(app, script);
+
+    assert!(screen.contains("Work phase done for task: Focus, time for a 5 min break <P>"));
+}
+
+#[test]
+fn the_switch_key_cycles_through_the_modes() {
+    let (app, clock) = setup();
+    let mut titles = Vec::new();
+    for presses in 0..5 {
+        let mut script = Script::new(&clock).key(KeyCode::Tab);
+        for _ in 0..presses {
+            script = script.key(KeyCode::Char('T'));
+        }
+        let (_, screen) = replay(App::new(app.tracker.clone()), script.key(KeyCode::Esc));
+        let title = ["Tasks", "Timer", "Reports", "Settings"]
+            .into_iter()
+            .find(|t| screen.contains(&format!("[Mode: {t}]")))
+            .unwrap();
+        titles.push(title);
+    }
+    assert_eq!(titles, ["Tasks", "Timer", "Reports", "Settings", "Tasks"]);
+}
+
+#[test]
+fn the_banner_hands_over_to_the_dashboard() {
+    let (app, clock) = setup();
+    let mut terminal = Terminal::new(TestBackend::new(240, 60)).unwrap();
+    let mut script = Script::new(&clock)
+        .key(KeyCode::Char('D'))
+        .key(KeyCode::Esc);
+    let app = tui_banner(&mut terminal, app, &mut script).unwrap();
+    assert!(app.is_some());
+    assert!(screen(&terminal).contains("[Mode: Tasks]"));
+
+    let mut script = Script::new(&clock).key(KeyCode::Esc);
+    let app = tui_banner(&mut terminal, app.unwrap(), &mut script).unwrap();
+    assert!(app.is_none());
+    assert!(screen(&terminal).contains("Press <D> to jump to DASHBOARD"));
+}
+
+#[test]
+fn time_on_the_banner_is_not_mistaken_for_a_suspend() {
+    let (mut app, clock) = setup();
+    app.tracker.start(TaskSpec::parse("Focus")).unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(240, 60)).unwrap();
+    let mut script = Script::new(&clock)
+        .wait(5)
+        .key(KeyCode::Char('D'))
+        .key(KeyCode::Esc);
+    let app = tui_banner(&mut terminal, app, &mut script)
+        .unwrap()
+        .unwrap();
+    assert!(app.away.is_none());
+    assert!(!screen(&terminal).contains("YOU WERE AWAY"));
+}
+
+#[test]
+fn clicking_tabs_and_menu_entries() {
+    let (app, clock) = setup();
+    let (app, screen) = replay(app, Script::new(&clock).key(KeyCode::Tab).key(KeyCode::Esc));
+    let timer_tab = find(&screen, "[Timer]");
+
+    let script = Script::new(&clock)
+        .key(KeyCode::Tab)
+        .click(timer_tab)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("[Mode: Timer]"));
+    let switch_view = find(&screen, "Switch View");
+
+    let script = Script::new(&clock)
+        .click(timer_tab)
+        .click(switch_view)
+        .key(KeyCode::Esc);
+    let (_, screen) = replay(app, script);
+    assert!(screen.contains("[Mode: Reports]"));
+}
+
+#[test]
+fn clicking_and_wheeling_through_task_rows() {
+    let (app, clock) = setup();
+    let script = Script::new(&clock)
+        .text("Alpha")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Tab)
+        .text("Bravo")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Tab)
+        .text("Charlie")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert_eq!(app.selected_index, Some(2));
+    let alpha = find(&screen, "Alpha");
+    let bravo = find(&screen, "Bravo");
+
+    let script = Script::new(&clock).click(bravo).key(KeyCode::Esc);
+    let (app, _) = replay(app, script);
+    assert_eq!(app.selected_index, Some(1));
+    assert!(!app.focus_textarea);
+
+    let script = Script::new(&clock)
+        .mouse(MouseEventKind::ScrollDown, alpha)
+        .key(KeyCode::Esc);
+    let (app, _) = replay(app, script);
+    assert_eq!(app.selected_index, Some(2));
+
+    let script = Script::new(&clock)
+        .mouse(MouseEventKind::ScrollUp, alpha)
+        .mouse(MouseEventKind::ScrollUp, alpha)
+        .key(KeyCode::Esc);
+    let (app, _) = replay(app, script);
+    assert_eq!(app.selected_index, Some(0));
+}
+
+#[test]
+fn paging_and_sorting_a_long_task_list() {
+    let (app, clock) = setup();
+    let mut tracker = app.tracker;
+    for n in 1..=40 {
+        tracker
+            .add_task(
+                TaskSpec::parse(&format!("Job {:02}", 41 - n)),
+                TaskStatus::Paused,
+            )
+            .unwrap();
+    }
+    let app = App::new(tracker);
+
+    let script = Script::new(&clock).key(KeyCode::End).key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert_eq!(app.selected_index, Some(39));
+    assert!(screen.contains(" 40/40 "));
+    assert!(screen.contains("Job 01"), "the last row scrolled into view");
+    assert!(!screen.contains("Job 40"));
+
+    let script = Script::new(&clock).key(KeyCode::Home).key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains(" 1/40 "));
+    assert!(screen.contains("Job 40"));
+    let page = app.task_page;
+
+    let script = Script::new(&clock).key(KeyCode::PageDown).key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains(&format!(" {}/40 ", page + 1)));
+
+    // The sort key turns the current column around before moving on
+    let script = Script::new(&clock)
+        .key(KeyCode::Char('S'))
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("[ID ▼]"));
+
+    // Sorting by name puts "Job 01", the last task added, first
+    let script = Script::new(&clock)
+        .key(KeyCode::Char('S'))
+        .key(KeyCode::Home)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("[Task Name ▲]"));
+    assert_eq!(app.selected_index, Some(39));
+
+    // A click on the sorted header flips the direction
+    let header = find(&screen, "[Task Name ▲]");
+    let script = Script::new(&clock)
+        .click(header)
+        .key(KeyCode::Home)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("[Task Name ▼]"));
+    assert_eq!(app.selected_index, Some(0));
+
+    // The Timer view pages its own table and skips the time column it lacks
+    let script = Script::new(&clock)
+        .key(KeyCode::Char('T'))
+        .key(KeyCode::PageDown)
+        .key(KeyCode::Char('S'))
+        .key(KeyCode::Char('S'))
+        .key(KeyCode::Char('S'))
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("[Mode: Timer]"));
+    assert!(screen.contains("[ID ▲]"), "Status ▼ wraps around to ID");
+    assert_eq!(app.selected_index, Some(app.task_page));
+}
+
+#[test]
+fn the_logs_page_with_their_own_keys() {
+    let (mut app, clock) = with_tasks(&["Alpha"]);
+    let (id, now) = (app.tracker.tasks[0].id, app.tracker.now());
+    let mut breaks = Break::new();
+    for minutes in 1..=100 {
+        breaks.history.push(BreakRecord {
+            planned: Duration::from_secs(5 * 60),
+            actual: Duration::from_secs(minutes * 60),
+            ended_at: now,
+        });
+    }
+    app.tracker.breaks.insert(id, breaks);
+    // Every replay starts out in the Tasks view
+    let logs = |code| {
+        Script::new(&clock)
+            .key(KeyCode::Char('T'))
+            .key_with(code, KeyModifiers::SHIFT)
+            .key(KeyCode::Esc)
+    };
+
+    let (app, screen) = replay(app, logs(KeyCode::PageDown));
+    let page = app.logs_page;
+    assert_eq!(app.logs_scroll, page);
+    assert!(screen.contains(&format!(" {}/100 ", page + 1)));
+    assert!(screen.contains(&format!("Break {} task_name: Alpha", page + 1)));
+    assert_eq!(app.selected_index, Some(0), "the task table stays put");
+
+    let (app, screen) = replay(app, logs(KeyCode::End));
+    assert_eq!(app.logs_scroll, 100 - page);
+    assert!(screen.contains("Break 100 task_name: Alpha"));
+
+    let (app, _) = replay(app, logs(KeyCode::PageUp));
+    assert_eq!(app.logs_scroll, 100 - 2 * page);
+
+    let (app, screen) = replay(app, logs(KeyCode::Home));
+    assert_eq!(app.logs_scroll, 0);
+    assert!(screen.contains(" 1/100 "));
+}
+
+/// A fresh app with paused tasks of these names.
+fn with_tasks(names: &[&str]) -> (App, MockClock) {
+    let (app, clock) = setup();
+    let mut tracker = app.tracker;
+    for name in names {
+        tracker
+            .add_task(TaskSpec::parse(name), TaskStatus::Paused)
+            .unwrap();
+    }
+    (App::new(tracker), clock)
+}
+
+#[test]
+fn the_slash_filter_narrows_the_table_as_you_type() {
+    let tasks = ["Write report", "Review PR", "Weekly planning"];
+    let (app, clock) = with_tasks(&tasks);
+    let screen = snapshot(
+        app,
+        Script::new(&clock).key(KeyCode::Char('/')).text("plan"),
+    );
+    assert!(screen.contains("[/plan_]"));
+    assert!(screen.contains("Weekly planning"));
+    assert!(!screen.contains("Write report"));
+
+    // Enter keeps the filter, in the Timer view too
+    let (app, clock) = with_tasks(&tasks);
+    let script = Script::new(&clock)
+        .key(KeyCode::Char('/'))
+        .text("wrep")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Char('T'));
+    let screen = snapshot(app, script);
+    assert!(screen.contains("TASKS [/wrep]"));
+    assert!(screen.contains("Write report"));
+    assert!(!screen.contains("Review PR"));
+
+    // The first Esc drops the filter, the second quits
+    let (app, clock) = with_tasks(&tasks);
+    let script = Script::new(&clock)
+        .key(KeyCode::Char('/'))
+        .text("plan")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Esc)
+        .key(KeyCode::Esc);
+    let (app, _) = replay(app, script);
+    assert_eq!(
+        app.selected_index,
+        Some(2),
+        "the selection followed the filter"
+    );
+    assert!(app.search.query.is_empty());
+}
+
+#[test]
+fn a_suggestion_resumes_the_task_instead_of_adding_one() {
+    let (app, clock) = with_tasks(&["Write report", "Review PR"]);
+    let script = Script::new(&clock)
+        .key(KeyCode::Tab)
+        .text("writ repot")
+        .key(KeyCode::Down)
+        .key(KeyCode::Esc);
+    let (_, screen) = replay(app, script);
+    assert!(screen.contains("EXISTING TASKS"));
+    assert!(screen.contains("Write report (Paused)"));
+
+    let (app, clock) = with_tasks(&["Write report", "Review PR"]);
+    let script = Script::new(&clock)
+        .key(KeyCode::Tab)
+        .text("writ repot")
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert_eq!(app.tracker.tasks.len(), 2);
+    assert_eq!(app.tracker.tasks[0].status, TaskStatus::Active);
+    assert_eq!(app.selected_index, Some(0));
+    assert!(!screen.contains("EXISTING TASKS"));
+
+    // Picking the task that is already running only selects it
+    let (mut app, clock) = with_tasks(&["Write report", "Review PR"]);
+    app.tracker.resume(0);
+    app.selected_index = Some(1);
+    let script = Script::new(&clock)
+        .key(KeyCode::Tab)
+        .text("writ repot")
+        .key(KeyCode::Down)
+        .key(KeyCode::Enter);
+    let screen = snapshot(app, script);
+    assert!(screen.contains("'Write report' is already running"));
+    assert!(!screen.contains("WARNING"));
+}
+
+#[test]
+fn deleting_a_task_leaves_the_others_breaks_alone() {
+    let (mut app, clock) = with_tasks(&["Inbox", "Focus"]);
+    app.tracker.resume(1);
+    app.tracker.pause(1, None);
+    let focus = app.tracker.tasks[1].id;
+    app.selected_index = Some(0);
+    let script = Script::new(&clock)
+        .wait(2)
+        .key(KeyCode::Delete)
+        .key(KeyCode::Char('T'))
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+
+    assert_eq!(app.tracker.tasks[0].id, focus);
+    assert!(screen.contains(
+        "Break_duration: 00:02:00 / 00:05:00 task_name: Focus short_break_status: Ongoing"
+    ));
+    assert!(!screen.contains("task_name: Inbox"));
+    assert_eq!(app.tracker.trash[0].breaks, None);
+}
+
+#[test]
+fn settings_are_edited_in_place_and_checked() {
+    let (app, clock) = setup();
+    let to_settings = |script: Script| {
+        script
+            .key(KeyCode::Tab)
+            .key(KeyCode::Char('T'))
+            .key(KeyCode::Char('T'))
+            .key(KeyCode::Char('T'))
+    };
+    // The last row is ui.chart_max_minutes
+    let script = to_settings(Script::new(&clock))
+        .key(KeyCode::End)
+        .key(KeyCode::Enter)
+        .key(KeyCode::Backspace)
+        .key(KeyCode::Backspace)
+        .text("45")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("[Mode: Settings]"));
+    assert!(screen.contains("Changed ui.chart_max_minutes for this session"));
+    assert_eq!(app.config.ui.chart_max_minutes, 45);
+
+    let (app, clock) = setup();
+    let script = to_settings(Script::new(&clock))
+        .key(KeyCode::End)
+        .key(KeyCode::Enter)
+        .key(KeyCode::Backspace)
+        .key(KeyCode::Backspace)
+        .text("0")
+        .key(KeyCode::Enter);
+    let screen = snapshot(app, script);
+    assert!(screen.contains("EDIT ui.chart_max_minutes"));
+    assert!(screen.contains("ui.chart_max_minutes must be at least 1, got 0"));
+}
+
+#[test]
+fn the_session_saves_as_it_goes_and_loads_what_others_saved() {
+    let path = std::env::temp_dir()
+        .join(format!("chronos-{}-session", std::process::id()))
+        .join("tasks.json");
+    let autosave = || {
+        let mut store = Autosave::new(path.clone());
+        store.load().unwrap();
+        Some(store)
+    };
+
+    // No quit: the task is on disk before the session ends
+    let (mut app, clock) = setup();
+    app.autosave = autosave();
+    let script = Script::new(&clock).text("Write").key(KeyCode::Enter);
+    snapshot(app, script);
+    let saved = storage::load(&path).unwrap();
+    assert_eq!(saved.tasks[0].name, "Write");
+
+    // `chronos start` run while the TUI is open
+    let (mut app, clock) = setup();
+    app.autosave = autosave();
+    let mut elsewhere = storage::load(&path).unwrap();
+    elsewhere
+        .add_task(TaskSpec::parse("Review"), TaskStatus::Active)
+        .unwrap();
+    storage::save(&path, &elsewhere).unwrap();
+    let (app, screen) = replay(app, Script::new(&clock).key(KeyCode::Esc));
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    assert_eq!(app.tracker.tasks.len(), 2);
+    assert!(screen.contains("Review"));
+    assert!(screen.contains("Loaded the changes saved from outside"));
+}
+
+#[test]
+fn a_layout_row_is_edited_and_the_body_makes_room() {
+    let (app, clock) = setup();
+    let row = app
+        .config
+        .entries()
+        .iter()
+        .position(|(key, _)| key == "layout.footer")
+        .unwrap();
+    let mut script = Script::new(&clock)
+        .key(KeyCode::Tab)
+        .key(KeyCode::Char('T'))
+        .key(KeyCode::Char('T'))
+        .key(KeyCode::Char('T'));
+    for _ in 0..row {
+        script = script.key(KeyCode::Down);
+    }
+    let script = script
+        .key(KeyCode::Enter)
+        .key(KeyCode::Backspace)
+        .key(KeyCode::Backspace)
+        .text("5")
+        .key(KeyCode::Enter)
+        .key(KeyCode::Esc);
+    let (app, screen) = replay(app, script);
+    assert!(screen.contains("Changed layout.footer for this session"));
+    assert_eq!((app.config.layout.footer, app.config.layout.body), (5, 75));
+}
+
+/// "Write" running and "Email" paused, under the vi preset.
+fn vi_with_write_and_email() -> (App, MockClock) {
+    let (mut app, clock) = setup();
+    let vi = KeysConfig {
+        preset: Preset::Vi,
+        ..KeysConfig::default()
+    };
+    app.keymap = Keymap::new(&vi).unwrap();
+    app.tracker.start(TaskSpec::parse("Write")).unwrap();
+    app.tracker
+        .add_task(TaskSpec::parse("Email"), TaskStatus::Paused)
+        .unwrap();
+    (app, clock)
+}
+
+#[test]
+fn the_away_prompt_takes_its_keys_from_the_keymap() {
+    let (app, clock) = vi_with_write_and_email();
+    let script = Script::new(&clock)
+        .key(KeyCode::Tab)
+        .key(KeyCode::Char('?'));
+    let screen = snapshot(app, script);
+    assert!(screen.contains("AWAY PROMPT"));
+    assert!(screen.contains("<k> <Esc>"));
+
+    let (app, clock) = vi_with_write_and_email();
+    let screen = snapshot(app, Script::new(&clock).wait(11));
+    assert!(screen.contains("<k> Keep it   <d> Discard it   <b> Count it as a break"));
+    assert!(screen.contains("<r> Reassign it to: < Write >   <h>/<l> Choose"));
+
+    // `R` is the default preset's, so it leaves the prompt open
+    let (app, clock) = vi_with_write_and_email();
+    let script = Script::new(&clock)
+        .wait(11)
+        .key(KeyCode::Char('R'))
+        .key(KeyCode::Char('l'))
+        .key(KeyCode::Char('r'))
+        .key(KeyCode::Esc);
+    let (app, _) = replay(app, script);
+    assert!(app.away.is_none());
+    let email = app.tracker.tasks[1].id;
+    let moved = app.tracker.intervals.iter().find(|iv| iv.task_id == email);
+    assert!(
+        moved
+            .unwrap()
+            .note
+            .as_deref()
+            .unwrap()
+            .contains("moved to 'Email'")
+    );
+}
@@ -1,81 +1,630 @@
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use crate::command::Prompt;
+use crate::config::Config;
+use crate::history::History;
+use crate::idle::Away;
+use crate::keymap::Keymap;
+use crate::mouse::Hits;
+use crate::report::{Breakdown, Period};
+use crate::search::{self, Search};
+use crate::settings_ui::Settings;
+use crate::storage::Autosave;
+use crate::tracker::{Task, TaskId, TaskSpec, TaskStatus, Tracker};
+use chrono::{DateTime, Days, Local, NaiveDate, NaiveTime, TimeZone};
+use ratatui::widgets::TableState;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
 use tui_textarea::TextArea;
-#[derive(Debug, Clone)]
-pub struct Task {
-    pub id: u32,
-    pub name: String,
-    pub status: TaskStatus,
-    pub time_spent: Duration,
-    pub started_at: Option<Instant>,
-    pub expected_duration: Duration,
+
+/// How the TASKS table and bar graph bundle tasks together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    None,
+    Project,
+    Tag,
+}
+
+impl GroupBy {
+    pub fn next(self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::Project,
+            GroupBy::Project => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::None,
+        }
+    }
+}
+
+/// A bundle of tasks sharing a project or tag, with the time tracked on them.
+pub struct TaskGroup {
+    pub key: String,
+    pub tasks: Vec<usize>,
+    /// The rows the TASKS table shows as `(task index, depth)`: subtasks
+    /// follow their parent, collapsed subtrees are left out.
+    pub rows: Vec<(usize, usize)>,
+    pub total: Duration,
 }
 
-#[derive(Debug, Clone)]
-pub enum TaskStatus {
-    Active,
-    Paused,
+/// Column the TASKS table is sorted by. Subtasks stay under their parent
+/// and are sorted among their siblings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    Id,
+    Name,
+    Status,
+    Time,
 }
 
+impl SortBy {
+    /// The columns of the TASKS table in the Tasks view.
+    pub const TASKS: [SortBy; 4] = [SortBy::Id, SortBy::Name, SortBy::Status, SortBy::Time];
+    /// The Timer view's TASKS table, which has no time column.
+    pub const TIMER: [SortBy; 3] = [SortBy::Id, SortBy::Name, SortBy::Status];
+}
+
+/// Everything the TUI keeps: the tracked data in `tracker` plus the state
+/// of the views around it.
 pub struct App {
-    pub tasks: Vec<Task>,
-    pub next_id: u32,
+    pub tracker: Tracker,
     pub textarea: TextArea<'static>,
     pub focus_textarea: bool,
     pub selected_index: Option<usize>,
     pub show_popup: bool,
-    pub show_green_log: bool,
-    pub green_log_task: Option<String>,
-    pub breaks: HashMap<u32, Break>,
+    /// Id of the task the Timer view last saw start.
+    pub green_log_task: Option<TaskId>,
+    /// Snapshots for undo/redo, see `history`.
+    pub history: History,
+    /// Id of the task whose name the EDIT TASK box is renaming.
+    pub renaming: Option<TaskId>,
+    /// Ids of the parents whose subtasks the TASKS table hides.
+    pub collapsed: HashSet<TaskId>,
+    /// Whether the bar graph shows leaf tasks instead of top-level totals.
+    pub graph_leaves: bool,
+    /// How often the views redraw while waiting for input.
+    pub tick_rate: Duration,
+    /// Speed of the simulated clock when run with `--simulate`.
+    pub simulate: Option<u32>,
+    pub config: Config,
+    /// Where the Settings view saves `config`; `None` keeps its changes to
+    /// this session.
+    pub config_path: Option<PathBuf>,
+    /// Bindings built from `config.keys`.
+    pub keymap: Keymap,
+    /// Whether the `?` key list is showing.
+    pub show_help: bool,
+    pub group_by: GroupBy,
+    pub sort_by: SortBy,
+    pub sort_descending: bool,
+    /// Group key (`+project` / `@tag`) the task list is narrowed to.
+    pub group_filter: Option<String>,
+    /// The `/` filter over task names.
+    pub search: Search,
+    /// Highlighted entry of the textarea's suggestions.
+    pub suggestion: Option<usize>,
+    pub report_period: Period,
+    pub report_breakdown: Breakdown,
+    /// How many ranges back from today the Reports view is looking.
+    pub report_offset: u32,
+    /// One-line outcome of the last command, shown until the next one.
+    pub status_message: Option<String>,
+    /// The `:` command line in the COMMAND bar.
+    pub prompt: Prompt,
+    pub last_input: DateTime<Local>,
+    pub last_tick: DateTime<Local>,
+    /// Gap waiting for a keep/discard/reassign decision.
+    pub away: Option<Away>,
+    /// What the mouse can hit on the screen last drawn.
+    pub hits: Hits,
+    /// Scroll position of the TASKS table, kept on the selected row.
+    pub task_table: TableState,
+    /// How many Productivity Logs lines are scrolled off the top.
+    pub logs_scroll: usize,
+    /// Rows the TASKS table and the logs fit on the last frame, for paging.
+    pub task_page: usize,
+    pub logs_page: usize,
+    pub settings: Settings,
+    /// Saves `tracker` as the session goes; `None` keeps every change in
+    /// memory, as under `--simulate` and in tests.
+    pub autosave: Option<Autosave>,
 }
 impl App {
-    pub fn new() -> Self {
+    /// Wraps loaded data, with the first task selected if there is one.
+    pub fn new(tracker: Tracker) -> Self {
+        let empty = tracker.tasks.is_empty();
+        let now = tracker.now();
         Self {
-            tasks: Vec::new(),
-            next_id: 1,
+            tracker,
             textarea: TextArea::default(),
-            focus_textarea: true,
-            selected_index: None,
+            focus_textarea: empty,
+            selected_index: if empty { None } else { Some(0) },
             show_popup: false,
-            show_green_log: false,
             green_log_task: None,
-            breaks: HashMap::new(),
+            history: History::default(),
+            renaming: None,
+            collapsed: HashSet::new(),
+            graph_leaves: false,
+            tick_rate: Duration::from_secs(1),
+            simulate: None,
+            config: Config::default(),
+            config_path: None,
+            keymap: Keymap::default(),
+            show_help: false,
+            group_by: GroupBy::None,
+            sort_by: SortBy::Id,
+            sort_descending: false,
+            group_filter: None,
+            search: Search::default(),
+            suggestion: None,
+            report_period: Period::Day,
+            report_breakdown: Breakdown::Task,
+            report_offset: 0,
+            status_message: None,
+            prompt: Prompt::default(),
+            last_input: now,
+            last_tick: now,
+            away: None,
+            hits: Hits::default(),
+            task_table: TableState::default(),
+            logs_scroll: 0,
+            task_page: 1,
+            logs_page: 1,
+            settings: Settings::default(),
+            autosave: None,
+        }
+    }
+
+    /// Keys of the groups a task falls into under the current grouping. With
+    /// tags a task can sit in several groups at once.
+    pub fn group_keys(&self, task: &Task) -> Vec<String> {
+        match self.group_by {
+            GroupBy::None => vec![String::new()],
+            GroupBy::Project => vec![match &task.project {
+                Some(project) => format!("+{project}"),
+                None => "(no project)".to_string(),
+            }],
+            GroupBy::Tag if task.tags.is_empty() => vec!["(untagged)".to_string()],
+            GroupBy::Tag => task.tags.iter().map(|t| format!("@{t}")).collect(),
+        }
+    }
+
+    /// Tasks bundled by the current grouping, sorted by key, with the active
+    /// filters applied and the tasks in each in the current sort order.
+    pub fn task_groups(&self) -> Vec<TaskGroup> {
+        let mut groups: Vec<TaskGroup> = Vec::new();
+        for i in self.sorted_tasks() {
+            let task = &self.tracker.tasks[i];
+            if search::fuzzy_score(&self.search.query, &task.label()).is_none() {
+                continue;
+            }
+            for key in self.group_keys(task) {
+                if self.group_filter.as_ref().is_some_and(|f| *f != key) {
+                    continue;
+                }
+                match groups.iter_mut().find(|g| g.key == key) {
+                    Some(group) => group.tasks.push(i),
+                    None => groups.push(TaskGroup {
+                        key,
+                        tasks: vec![i],
+                        rows: Vec::new(),
+                        total: Duration::ZERO,
+                    }),
+                }
+            }
+        }
+        for group in &mut groups {
+            group.total = group
+                .tasks
+                .iter()
+                .map(|&i| self.tracker.time_spent(&self.tracker.tasks[i]))
+                .sum();
+            group.rows = self.tree_rows(&group.tasks);
+        }
+        groups.sort_by(|a, b| a.key.cmp(&b.key));
+        groups
+    }
+
+    /// Task indices in the current sort order.
+    fn sorted_tasks(&self) -> Vec<usize> {
+        let tasks = &self.tracker.tasks;
+        let mut order: Vec<usize> = (0..tasks.len()).collect();
+        match self.sort_by {
+            SortBy::Id => order.sort_by_key(|&i| tasks[i].number),
+            SortBy::Name => order.sort_by_key(|&i| tasks[i].name.to_lowercase()),
+            SortBy::Status => order.sort_by_key(|&i| tasks[i].status != TaskStatus::Active),
+            SortBy::Time => order.sort_by_cached_key(|&i| self.tracker.rolled_up_time(&tasks[i])),
+        }
+        if self.sort_descending {
+            order.reverse();
+        }
+        order
+    }
+
+    /// A TASKS column header, with an arrow on the column sorted by.
+    pub fn column_title(&self, title: &str, column: SortBy) -> String {
+        match (self.sort_by == column, self.sort_descending) {
+            (false, _) => format!("[{title}]"),
+            (true, false) => format!("[{title} ▲]"),
+            (true, true) => format!("[{title} ▼]"),
+        }
+    }
+
+    /// Steps the sort the way the sort key does: the current column goes
+    /// descending, then the next of `columns` takes over ascending.
+    pub fn cycle_sort(&mut self, columns: &[SortBy]) {
+        let at = columns.iter().position(|&c| c == self.sort_by);
+        match at {
+            Some(_) if !self.sort_descending => self.sort_descending = true,
+            _ => {
+                self.sort_by = at.map_or(columns[0], |n| columns[(n + 1) % columns.len()]);
+                self.sort_descending = false;
+            }
+        }
+    }
+
+    /// Sorts by `column`, or flips the direction when already sorted by it.
+    pub fn sort_on(&mut self, column: SortBy) {
+        if self.sort_by == column {
+            self.sort_descending = !self.sort_descending;
+        } else {
+            self.sort_by = column;
+            self.sort_descending = false;
+        }
+    }
+
+    /// Lays `members` out as a tree. A task whose parent is not among them
+    /// starts a tree of its own.
+    fn tree_rows(&self, members: &[usize]) -> Vec<(usize, usize)> {
+        let ids: Vec<TaskId> = members.iter().map(|&i| self.tracker.tasks[i].id).collect();
+        let mut rows = Vec::new();
+        let mut stack: Vec<(usize, usize)> = members
+            .iter()
+            .rev()
+            .filter(|&&i| {
+                self.tracker.tasks[i]
+                    .parent
+                    .is_none_or(|p| !ids.contains(&p))
+            })
+            .map(|&i| (i, 0))
+            .collect();
+        while let Some((i, depth)) = stack.pop() {
+            if rows.iter().any(|&(r, _)| r == i) {
+                continue;
+            }
+            rows.push((i, depth));
+            let id = self.tracker.tasks[i].id;
+            if self.collapsed.contains(&id) {
+                continue;
+            }
+            stack.extend(
+                members
+                    .iter()
+                    .rev()
+                    .filter(|&&c| self.tracker.tasks[c].parent == Some(id))
+                    .map(|&c| (c, depth + 1)),
+            );
+        }
+        rows
+    }
+
+    /// Task indices in the order the TASKS table shows them, each task once.
+    pub fn visible_tasks(&self) -> Vec<usize> {
+        let mut visible = Vec::new();
+        for group in self.task_groups() {
+            for (i, _) in group.rows {
+                if !visible.contains(&i) {
+                    visible.push(i);
+                }
+            }
+        }
+        visible
+    }
+
+    /// Switches to the next grouping and drops the filter, which only made
+    /// sense for the old one.
+    pub fn cycle_group_by(&mut self) {
+        self.group_by = self.group_by.next();
+        self.group_filter = None;
+    }
+
+    /// Narrows the list to the next group in turn, then back to all of them.
+    pub fn cycle_group_filter(&mut self) {
+        if self.group_by == GroupBy::None {
+            return;
+        }
+        let current = self.group_filter.take();
+        let keys: Vec<String> = self.task_groups().into_iter().map(|g| g.key).collect();
+        self.group_filter = match current {
+            None => keys.first().cloned(),
+            Some(key) => keys
+                .iter()
+                .position(|k| *k == key)
+                .and_then(|p| keys.get(p + 1))
+                .cloned(),
+        };
+    }
+
+    pub fn select_next(&mut self) {
+        self.select_by(1);
+    }
+
+    pub fn select_prev(&mut self) {
+        self.select_by(-1);
+    }
+
+    /// Moves the selection `by` rows through the visible tasks, stopping at
+    /// the first and last.
+    pub fn select_by(&mut self, by: isize) {
+        let visible = self.visible_tasks();
+        if visible.is_empty() {
+            return;
+        }
+        let pos = match self.selected_position(&visible) {
+            Some(p) => p.saturating_add_signed(by).min(visible.len() - 1),
+            None => 0,
+        };
+        self.selected_index = Some(visible[pos]);
+    }
+
+    /// Where the selected task sits among `visible`, counting from 0.
+    pub fn selected_position(&self, visible: &[usize]) -> Option<usize> {
+        let i = self.selected_index?;
+        visible.iter().position(|&v| v == i)
+    }
+
+    /// Whether some box is taking text: the textarea, the `:` or `/` line,
+    /// a rename or a setting being edited.
+    pub fn typing(&self) -> bool {
+        self.focus_textarea
+            || self.prompt.active
+            || self.search.active
+            || self.renaming.is_some()
+            || self.settings.input.is_some()
+    }
+
+    /// The task named in the textarea, or the selected one when it is empty.
+    pub fn typed_or_selected(&self) -> Option<TaskSpec> {
+        let spec = TaskSpec::parse(&self.textarea.lines().join(" "));
+        if !spec.name.is_empty() {
+            return Some(spec);
+        }
+        let task = self.tracker.tasks.get(self.selected_index?)?;
+        Some(TaskSpec {
+            name: task.name.clone(),
+            estimate: None,
+            project: None,
+            tags: Vec::new(),
+            parents: Vec::new(),
+        })
+    }
+
+    /// Folds or unfolds the subtasks of task `i` in the TASKS table.
+    pub fn toggle_collapsed(&mut self, i: usize) {
+        let id = self.tracker.tasks[i].id;
+        if self.tracker.children(id).is_empty() || !self.collapsed.insert(id) {
+            self.collapsed.remove(&id);
         }
     }
 }
-#[derive(Debug, Clone)]
-pub enum BreakStatus {
-    Started,
-    Ongoing,
-    Completed,
-    Exceeded,
+
+/// Parses durations like `45m`, `1h30m`, `2h` or `90s`. A bare number is
+/// read as minutes.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    if let Ok(minutes) = input.parse::<u64>() {
+        return Some(Duration::from_secs(minutes.checked_mul(60)?)).filter(|d| !d.is_zero());
+    }
+
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: u64 = digits.parse().ok()?;
+        digits.clear();
+        let secs = match c {
+            'h' => n.checked_mul(3600)?,
+            'm' => n.checked_mul(60)?,
+            's' => n,
+            _ => return None,
+        };
+        total = total.checked_add(secs)?;
+    }
+    if !digits.is_empty() || total == 0 {
+        return None;
+    }
+    Some(Duration::from_secs(total))
 }
 
-pub struct Break {
-    pub break_status: BreakStatus,
-    pub break1_started_at: Option<Instant>,
-    pub break2_started_at: Option<Instant>,
-    pub break3_started_at: Option<Instant>,
-    pub break_interval_1: Duration,
-    pub break_interval_2: Duration,
-    pub break_interval_3: Duration,
-    pub elapsed_time_1: Duration,
-    pub elapsed_time_2: Duration,
-    pub elapsed_time_3: Duration,
+/// Parses a point in time like `14:00`, `yesterday 14:00` or
+/// `2026-10-17 14:00`. Without a day the time falls on `day`; `today`
+/// anchors the words `today` and `yesterday`.
+pub fn parse_when(input: &str, day: NaiveDate, today: NaiveDate) -> Option<DateTime<Local>> {
+    let input = input.trim().replacen('T', " ", 1);
+    let (date, time) = match input.rsplit_once(' ') {
+        Some((date, time)) => (parse_day(date.trim(), today)?, time),
+        None => (day, input.as_str()),
+    };
+    let time = NaiveTime::parse_from_str(time, "%H:%M").ok()?;
+    Local.from_local_datetime(&date.and_time(time)).earliest()
 }
-impl Break {
-    pub fn new() -> Self {
-        Self {
-            break_status: BreakStatus::Started,
-            break1_started_at: Some(Instant::now()),
-            break2_started_at: None,
-            break3_started_at: None,
-            break_interval_1: Duration::new(0, 0),
-            break_interval_2: Duration::new(0, 0),
-            break_interval_3: Duration::new(0, 0),
-            elapsed_time_1: Duration::new(0, 0),
-            elapsed_time_2: Duration::new(0, 0),
-            elapsed_time_3: Duration::new(0, 0),
+
+/// Parses a stretch of time like `14:00-16:00` or `yesterday 9:30–12:00`.
+/// The end is a bare time; one before the start falls on the next day.
+pub fn parse_span(
+    input: &str,
+    day: NaiveDate,
+    today: NaiveDate,
+) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    let (start, end) = input.rsplit_once(['-', '–'])?;
+    let start = parse_when(start, day, today)?;
+    let end = parse_when(end, start.date_naive(), today)?;
+    if end <= start {
+        return Some((start, end.checked_add_days(Days::new(1))?));
+    }
+    Some((start, end))
+}
+
+fn parse_day(input: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match input {
+        "today" => Some(today),
+        "yesterday" => today.checked_sub_days(Days::new(1)),
+        _ => NaiveDate::parse_from_str(input, "%Y-%m-%d").ok(),
+    }
+}
+
+/// Formats a duration as `HH:MM:SS`, the way every view shows tracked time.
+pub fn format_hms(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+
+    use std::sync::Arc;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(day: NaiveDate, h: u32, m: u32) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&day.and_hms_opt(h, m, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_duration_reads_units_and_bare_minutes() {
+        let mins = |m: u64| Some(Duration::from_secs(m * 60));
+        assert_eq!(parse_duration("45"), mins(45));
+        assert_eq!(parse_duration("45m"), mins(45));
+        assert_eq!(parse_duration("1h30m"), mins(90));
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        for bad in ["", "0", "0m", "1x", "h", "1h30", "1.5h", "-5m"] {
+            assert_eq!(parse_duration(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn parse_duration_refuses_values_that_overflow() {
+        assert_eq!(parse_duration("99999999999999999h"), None);
+        assert_eq!(parse_duration("999999999999999999"), None);
+        assert_eq!(parse_duration("18446744073709551615s1s"), None);
+    }
+
+    #[test]
+    fn parse_when_anchors_days() {
+        let today = date(2026, 10, 18);
+        let day = date(2026, 10, 10);
+        assert_eq!(parse_when("14:00", day, today), Some(at(day, 14, 0)));
+        assert_eq!(
+            parse_when("yesterday 9:30", day, today),
+            Some(at(date(2026, 10, 17), 9, 30))
+        );
+        assert_eq!(parse_when("today 08:05", day, today), Some(at(today, 8, 5)));
+        assert_eq!(
+            parse_when("2026-01-02T10:00", day, today),
+            Some(at(date(2026, 1, 2), 10, 0))
+        );
+        assert_eq!(parse_when("25:00", day, today), None);
+        assert_eq!(parse_when("someday 10:00", day, today), None);
+    }
+
+    #[test]
+    fn parse_span_rolls_an_early_end_into_the_next_day() {
+        let today = date(2026, 10, 18);
+        assert_eq!(
+            parse_span("14:00-16:00", today, today),
+            Some((at(today, 14, 0), at(today, 16, 0)))
+        );
+        assert_eq!(
+            parse_span("yesterday 23:00–01:00", today, today),
+            Some((at(date(2026, 10, 17), 23, 0), at(today, 1, 0)))
+        );
+        assert_eq!(parse_span("14:00", today, today), None);
+    }
+
+    /// Tasks with an hour each logged on the morning of 2 March 2026.
+    fn grouped_app(names: &[&str]) -> App {
+        let day = date(2026, 3, 2);
+        let mut tracker = Tracker::default();
+        tracker.clock = Arc::new(MockClock::new(at(day, 18, 0)));
+        for (h, name) in (8..).zip(names) {
+            let i = tracker
+                .add_task(TaskSpec::parse(name), TaskStatus::Paused)
+                .unwrap();
+            let id = tracker.tasks[i].id;
+            tracker
+                .add_interval(id, at(day, h, 0), at(day, h + 1, 0), None)
+                .unwrap();
+        }
+        App::new(tracker)
+    }
+
+    fn keys_and_totals(app: &App) -> Vec<(String, u64)> {
+        app.task_groups()
+            .into_iter()
+            .map(|g| (g.key, g.total.as_secs() / 3600))
+            .collect()
+    }
+
+    #[test]
+    fn tasks_group_by_project_or_tag_with_subtotals() {
+        let mut app = grouped_app(&[
+            "Write +acme @docs @urgent",
+            "Review +acme",
+            "Email @urgent",
+            "Idle",
+        ]);
+        let key = |k: &str, hours| (k.to_string(), hours);
+        assert_eq!(keys_and_totals(&app), [key("", 4)]);
+
+        app.cycle_group_by();
+        assert_eq!(
+            keys_and_totals(&app),
+            [key("(no project)", 2), key("+acme", 2)]
+        );
+
+        // A task with two tags counts toward both, but is listed once
+        app.cycle_group_by();
+        assert_eq!(
+            keys_and_totals(&app),
+            [key("(untagged)", 2), key("@docs", 1), key("@urgent", 2)]
+        );
+        assert_eq!(app.visible_tasks(), [1, 3, 0, 2]);
+    }
+
+    #[test]
+    fn the_group_filter_steps_through_the_groups_and_back() {
+        let mut app = grouped_app(&["Write +acme", "Review +acme", "Email"]);
+        app.cycle_group_filter();
+        assert_eq!(app.group_filter, None, "nothing to filter without grouping");
+
+        app.cycle_group_by();
+        let mut seen = Vec::new();
+        for _ in 0..3 {
+            app.cycle_group_filter();
+            seen.push((app.group_filter.clone(), app.visible_tasks()));
         }
+        assert_eq!(
+            seen,
+            [
+                (Some("(no project)".to_string()), vec![2]),
+                (Some("+acme".to_string()), vec![0, 1]),
+                (None, vec![2, 0, 1]),
+            ]
+        );
+
+        app.cycle_group_filter();
+        app.cycle_group_by();
+        assert_eq!(app.group_filter, None, "a new grouping drops the filter");
     }
 }